@@ -1,8 +1,10 @@
-/// NOTE: variable naming is based on notation in https://eprint.iacr.org/2020/527.pdf
+//! NOTE: variable naming is based on notation in https://eprint.iacr.org/2020/527.pdf
 
 use std::ops::{Div, Mul};
-use ark_ff::Field;
-use ark_ec::pairing::Pairing;
+use ark_ff::{Field, Zero};
+use ark_ec::{pairing::Pairing, Group};
+use ark_serialize::{CanonicalSerialize, CanonicalDeserialize};
+use ark_std::{rand::RngCore, UniformRand};
 use crate::utils::{get_omega, mul, div, scalar_mul, interpolate, evaluate};
 
 #[derive(Clone)]
@@ -17,6 +19,7 @@ pub struct UpdateKey<E: Pairing> {
     pub ui_commitment: Vec<E::G1>,
 }
 
+#[derive(Clone)]
 pub struct ProvingKey<E: Pairing> {
     pub crs: CRS<E>,
     pub update_key: UpdateKey<E>,
@@ -28,6 +31,23 @@ pub struct VerificationKey<E: Pairing> {
     pub a_commitment: E::G1,
 }
 
+// prover role: commits to vectors and proves positions
+#[derive(Clone)]
+pub struct AsvcProver<E: Pairing> {
+    pub degree: usize,
+    pub proving_key: ProvingKey<E>,
+}
+
+// verifier role: only keeps the powers of tau needed for subvectors of bounded size
+#[derive(Clone, CanonicalSerialize, CanonicalDeserialize)]
+pub struct AsvcVerifier<E: Pairing> {
+    pub degree: usize,
+    /// powers of tau in G1 to commit to the interpolated subvector
+    pub crs_g1: Vec<E::G1>,
+    /// powers of tau in G2 to commit to the vanishing polynomial of the indices
+    pub crs_g2: Vec<E::G2>,
+}
+
 pub struct ASVC<E: Pairing> {
     pub degree: usize,
     pub update_key: UpdateKey<E>,
@@ -41,12 +61,12 @@ impl<E: Pairing> ASVC<E> {
         let mut crs_g1: Vec<E::G1> = Vec::new();
         let mut crs_g2: Vec<E::G2> = Vec::new();
         for i in 0..degree+1 {
-            crs_g1.push(g1.mul(secret.pow(&[i as u64])));
-            crs_g2.push(g2.mul(secret.pow(&[i as u64])));
+            crs_g1.push(g1.mul(secret.pow([i as u64])));
+            crs_g2.push(g2.mul(secret.pow([i as u64])));
         }
 
         // a_commitment is X^n - 1 multiply by G1
-        let a_commitment: E::G1 = crs_g1[degree].mul(E::ScalarField::ONE) + crs_g1[0].mul(E::ScalarField::ONE);
+        let a_commitment: E::G1 = crs_g1[degree] - crs_g1[0];
 
         // ai_commitment is (X^n - 1) / (X - w^i) multiply by G1
         let mut ai_commitment = vec![g1; degree];
@@ -59,22 +79,23 @@ impl<E: Pairing> ASVC<E> {
 
         // ai_numerator is X^n - 1
         let mut ai_numerator = vec![E::ScalarField::ZERO; degree+1];
-        ai_numerator[0] = E::ScalarField::ONE;
+        ai_numerator[0] = -E::ScalarField::ONE;
         ai_numerator[degree] = E::ScalarField::ONE;
+        let omega = get_omega(&vec![E::ScalarField::ZERO; degree]);
         for i in 0..degree {
             // ai_denominator is X - w^i
-            let ai_denominator = vec![-get_omega(&vec![E::ScalarField::ZERO; degree]).pow([i as u64]), E::ScalarField::ONE];
+            let ai_denominator = vec![-omega.pow([i as u64]), E::ScalarField::ONE];
             let ai_polynomial = div(&ai_numerator, &ai_denominator).unwrap();
 
-            // li_polynomial is ai_polynomial / a'(w^i), where a'(w^i) = n * (w^i)
+            // li_polynomial is ai_polynomial / a'(w^i), where a'(w^i) = n / (w^i)
             let li_polynomial = scalar_mul(
                 &ai_polynomial,
-                (get_omega(&vec![E::ScalarField::ZERO; degree]).pow(&[i as u64])).div(E::ScalarField::from(degree as u64))
+                omega.pow([i as u64]).div(E::ScalarField::from(degree as u64))
             );
 
             // ui_polynomial is (li_polynomial - 1) / (X - w^i)
             let mut ui_numerator = li_polynomial.clone();
-            ui_numerator[0] = ui_numerator[0] - E::ScalarField::ONE;
+            ui_numerator[0] -= E::ScalarField::ONE;
             let ui_polynomial = div(&ui_numerator, &ai_denominator).unwrap();
 
             // commit according to crs_g1
@@ -116,38 +137,17 @@ impl<E: Pairing> ASVC<E> {
 
     // commit the lagrange polynomials to the vector
     pub fn vector_commit(&self, vector: &[E::ScalarField]) -> E::G1 {
-        // check that vector length is equal to li_commitment length
-        assert_eq!(vector.len(), self.proving_key.li_commitment.len());
-
-        // commit vector
-        let mut commitment = self.proving_key.crs.g1[0].mul(E::ScalarField::ZERO);
-        for i in 0..vector.len() {
-            commitment += self.proving_key.li_commitment[i] * vector[i];
-        }
-        commitment
+        commit_vector::<E>(&self.proving_key.li_commitment, vector)
     }
 
-    // prove multiple positions in the vector
-    pub fn prove_position(&self, indices: &[usize], vector: &[E::ScalarField]) -> E::G1 {
-        // numerator is lagrange interpolation of the vector
-        let points: Vec<E::ScalarField> = (0..vector.len()).map(|i| E::ScalarField::from(i as u32)).collect();
-        let numerator = interpolate(&points, &vector).unwrap();
-
-        // denominator is product of i in indices (X - w^i)
-        let omega = get_omega(&vec![E::ScalarField::ZERO; vector.len()]);
-        let mut denominator = vec![-omega.pow([indices[0] as u64]), E::ScalarField::ONE];
-        for i in 1..indices.len() {
-            denominator = mul(&denominator, &[-omega.pow([i as u64]), E::ScalarField::ONE]);
-        }
+    // update a vector commitment after the value at index changed by delta
+    pub fn update_commitment(&self, commitment: E::G1, index: usize, delta: E::ScalarField) -> E::G1 {
+        commitment + self.proving_key.li_commitment[index] * delta
+    }
 
-        // quotient is numerator divided by denominator, committed to G1
-        let quotient = div(&numerator, &denominator).unwrap();
-        let mut pi = self.proving_key.crs.g1[0] * quotient[0];
-        for i in 1..quotient.len() {
-            pi += self.proving_key.crs.g1[i] * quotient[i];
-        }
-        
-        pi
+    // prove multiple positions in the vector, given as distinct indices below the degree
+    pub fn prove_position(&self, indices: &[usize], vector: &[E::ScalarField]) -> Result<E::G1, &'static str> {
+        prove_subvector::<E>(self.degree, &self.proving_key.crs.g1, indices, vector)
     }
 
     // verify a subvector commitment
@@ -158,60 +158,272 @@ impl<E: Pairing> ASVC<E> {
         subvector: &[E::ScalarField],
         proof: E::G1
     ) -> bool {
-        //  denominator is product of i in indices (X - w^i)
-        let omega = get_omega(&vec![E::ScalarField::ZERO; subvector.len()]);
-        let mut denominator = vec![-omega.pow([indices[0] as u64]), E::ScalarField::ONE];
-        for i in 1..indices.len() {
-            denominator = mul(&denominator, &vec![-omega.pow([i as u64]), E::ScalarField::ONE]);
+        let crs = &self.verification_key.crs;
+        verify_subvector::<E>(self.degree, &crs.g1, &crs.g2, commitment, indices, subvector, proof)
+    }
+
+    // aggregate multiple proofs into one subvector commitment
+    pub fn aggregate_proof(&self, indices: &[usize], proofs: Vec<E::G1>) -> Result<E::G1, &'static str> {
+        aggregate_subvector_proofs::<E>(self.degree, indices, proofs)
+    }
+
+    // audit published keys against the CRS with randomized pairing checks
+    pub fn verify_keys<R: RngCore>(&self, rng: &mut R) -> Result<(), &'static str> {
+        let n = self.degree;
+        let crs = &self.verification_key.crs;
+        let (ai, li, ui) = (&self.update_key.ai_commitment, &self.proving_key.li_commitment, &self.update_key.ui_commitment);
+        if crs.g1.len() != n + 1 || crs.g2.len() != n + 1 || ai.len() != n || li.len() != n || ui.len() != n {
+            return Err("Key lengths do not match the degree");
+        }
+        if self.proving_key.crs.g1 != crs.g1
+            || self.proving_key.crs.g2 != crs.g2
+            || self.proving_key.update_key.ai_commitment != *ai
+            || self.proving_key.update_key.ui_commitment != *ui
+        {
+            return Err("Proving and verification keys disagree");
         }
-        
-        // commit denominator 
-        let mut denominator_commitment = self.verification_key.crs.g2[0].mul(E::ScalarField::ZERO);
-        for i in 0..denominator.len() {
-            denominator_commitment += self.verification_key.crs.g2[i] * denominator[i];
+        let (g1, g2, g2_tau) = (crs.g1[0], crs.g2[0], crs.g2[1]);
+
+        // crs_g1[i+1] = tau * crs_g1[i] and crs_g2[i+1] = tau * crs_g2[i]
+        let rho: Vec<E::ScalarField> = (0..n).map(|_| E::ScalarField::rand(rng)).collect();
+        let lhs = E::pairing(combine::<E::G1>(&rho, &crs.g1[1..]), g2);
+        let rhs = E::pairing(combine::<E::G1>(&rho, &crs.g1[..n]), g2_tau);
+        if lhs != rhs {
+            return Err("CRS G1 powers are inconsistent");
+        }
+        let lhs = E::pairing(g1, combine::<E::G2>(&rho, &crs.g2[1..]));
+        let rhs = E::pairing(crs.g1[1], combine::<E::G2>(&rho, &crs.g2[..n]));
+        if lhs != rhs {
+            return Err("CRS G2 powers are inconsistent");
+        }
+
+        // a(X) = X^n - 1
+        if self.verification_key.a_commitment != crs.g1[n] - g1 {
+            return Err("Vanishing polynomial commitment is malformed");
         }
 
-        // remainder is the product of the lagrange basis of the indices
-        let indices_field: Vec<E::ScalarField> = indices.iter().map(|&i| E::ScalarField::from(i as u32)).collect();
-        let remainder = interpolate(&indices_field, &subvector).unwrap();
+        // a_i(X) (X - w^i) = a(X), checked as e(a_i, tau) = e(a + w^i a_i, 1)
+        let omega = get_omega(&vec![E::ScalarField::ZERO; n]);
+        let omegas: Vec<E::ScalarField> = (0..n).map(|i| omega.pow([i as u64])).collect();
+        let rho: Vec<E::ScalarField> = (0..n).map(|_| E::ScalarField::rand(rng)).collect();
+        let shifted: Vec<E::G1> = ai.iter().zip(&omegas).map(|(a, w)| self.verification_key.a_commitment + a.mul(w)).collect();
+        if E::pairing(combine::<E::G1>(&rho, ai), g2_tau) != E::pairing(combine::<E::G1>(&rho, &shifted), g2) {
+            return Err("Vanishing quotient commitments are malformed");
+        }
+
+        // L_i(X) = a_i(X) * w^i / n
+        let rho: Vec<E::ScalarField> = (0..n).map(|_| E::ScalarField::rand(rng)).collect();
+        let n_inv = E::ScalarField::from(n as u64).inverse().unwrap();
+        let scaled: Vec<E::G1> = ai.iter().zip(&omegas).map(|(a, w)| a.mul(*w * n_inv)).collect();
+        if combine::<E::G1>(&rho, li) != combine::<E::G1>(&rho, &scaled) {
+            return Err("Lagrange commitments are malformed");
+        }
 
-        // commit remainder
-        let mut remainder_commitment = self.verification_key.crs.g1[0].mul(E::ScalarField::ZERO);
-        for i in 0..remainder.len() {
-            remainder_commitment += self.verification_key.crs.g1[i] * remainder[i];
+        // u_i(X) (X - w^i) = L_i(X) - 1, checked as e(u_i, tau) = e(L_i - 1 + w^i u_i, 1)
+        let rho: Vec<E::ScalarField> = (0..n).map(|_| E::ScalarField::rand(rng)).collect();
+        let shifted: Vec<E::G1> = ui.iter().zip(li).zip(&omegas).map(|((u, l), w)| *l - g1 + u.mul(w)).collect();
+        if E::pairing(combine::<E::G1>(&rho, ui), g2_tau) != E::pairing(combine::<E::G1>(&rho, &shifted), g2) {
+            return Err("Update commitments are malformed");
         }
 
-        // verification 
-        let lhs = E::pairing(proof, denominator_commitment);
-        let rhs = E::pairing(commitment - remainder_commitment, self.verification_key.crs.g2[0]);
-        lhs == rhs
+        Ok(())
     }
 
-    // aggregate multiple proofs into one subvector commitment
-    pub fn aggregate_proof(&self, indices: &[usize], proofs: Vec<E::G1>) -> E::G1 {
-        // make sure that length of indices  is the same as the proofs
-        assert_eq!(indices.len(), proofs.len());
-
-        // A(X) is product of i in indices (X - w^i)
-        let omega = get_omega(&vec![E::ScalarField::ZERO; proofs.len()]);
-        let mut a_polynomial = vec![-omega.pow([indices[0] as u64]), E::ScalarField::ONE];
-        for i in 1..indices.len() {
-            a_polynomial = mul(&a_polynomial, &[-omega.pow([i as u64]), E::ScalarField::ONE]);
+    // extract the prover role, which keeps the full proving key
+    pub fn prover(&self) -> AsvcProver<E> {
+        AsvcProver {
+            degree: self.degree,
+            proving_key: self.proving_key.clone(),
         }
+    }
 
-        // A'(X), derivative of A(X)
-        let mut a_derivative = vec![E::ScalarField::ZERO; a_polynomial.len() - 1];
-        for i in 1..a_polynomial.len() {
-            a_derivative[i-1] = a_polynomial[i] * E::ScalarField::from(i as u32);
+    // extract a verifier role that checks subvectors of up to max_subvector_size positions
+    pub fn verifier(&self, max_subvector_size: usize) -> AsvcVerifier<E> {
+        assert!(max_subvector_size > 0 && max_subvector_size <= self.degree);
+        let crs = &self.verification_key.crs;
+        AsvcVerifier {
+            degree: self.degree,
+            crs_g1: crs.g1[..max_subvector_size].to_vec(),
+            crs_g2: crs.g2[..max_subvector_size + 1].to_vec(),
         }
+    }
+}
 
-        let pi = indices.iter().enumerate().map(|(k, &i)|{
-            proofs[k].mul(evaluate(&a_derivative, omega.pow([i as u64])))
-        }).sum::<E::G1>();
+impl<E: Pairing> AsvcProver<E> {
+    pub fn vector_commit(&self, vector: &[E::ScalarField]) -> E::G1 {
+        commit_vector::<E>(&self.proving_key.li_commitment, vector)
+    }
+
+    pub fn update_commitment(&self, commitment: E::G1, index: usize, delta: E::ScalarField) -> E::G1 {
+        commitment + self.proving_key.li_commitment[index] * delta
+    }
+
+    pub fn prove_position(&self, indices: &[usize], vector: &[E::ScalarField]) -> Result<E::G1, &'static str> {
+        prove_subvector::<E>(self.degree, &self.proving_key.crs.g1, indices, vector)
+    }
 
-        pi
+    pub fn aggregate_proof(&self, indices: &[usize], proofs: Vec<E::G1>) -> Result<E::G1, &'static str> {
+        aggregate_subvector_proofs::<E>(self.degree, indices, proofs)
+    }
+}
+
+impl<E: Pairing> AsvcVerifier<E> {
+    pub fn max_subvector_size(&self) -> usize {
+        self.crs_g1.len()
+    }
+
+    // verify a subvector commitment, rejecting subvectors larger than the key supports
+    pub fn verify_position(
+        &self,
+        commitment: E::G1,
+        indices: &[usize],
+        subvector: &[E::ScalarField],
+        proof: E::G1
+    ) -> bool {
+        if indices.len() > self.max_subvector_size() {
+            return false;
+        }
+        verify_subvector::<E>(self.degree, &self.crs_g1, &self.crs_g2, commitment, indices, subvector, proof)
+    }
+}
+
+// random linear combination of group elements
+fn combine<G: Group>(scalars: &[G::ScalarField], elements: &[G]) -> G {
+    scalars.iter().zip(elements).map(|(scalar, element)| element.mul(scalar)).sum()
+}
+
+// commit the lagrange polynomials to the vector
+fn commit_vector<E: Pairing>(li_commitment: &[E::G1], vector: &[E::ScalarField]) -> E::G1 {
+    // check that vector length is equal to li_commitment length
+    assert_eq!(vector.len(), li_commitment.len());
+
+    // commit vector
+    let mut commitment = E::G1::zero();
+    for i in 0..vector.len() {
+        commitment += li_commitment[i] * vector[i];
+    }
+    commitment
+}
+
+// a subvector is given by at least one index, all distinct and below the degree, as
+// indices past it would alias other positions through w^i
+fn check_indices(degree: usize, indices: &[usize]) -> Result<(), &'static str> {
+    if indices.is_empty() {
+        return Err("No indices given");
+    }
+    if indices.iter().any(|&i| i >= degree) {
+        return Err("Index out of range");
+    }
+    let mut sorted = indices.to_vec();
+    sorted.sort_unstable();
+    if sorted.windows(2).any(|pair| pair[0] == pair[1]) {
+        return Err("Duplicate index");
+    }
+    Ok(())
+}
+
+// prove multiple positions in the vector
+fn prove_subvector<E: Pairing>(
+    degree: usize,
+    crs_g1: &[E::G1],
+    indices: &[usize],
+    vector: &[E::ScalarField]
+) -> Result<E::G1, &'static str> {
+    check_indices(degree, indices)?;
+
+    // numerator is lagrange interpolation of the vector over the roots of unity
+    let omega = get_omega(&vec![E::ScalarField::ZERO; degree]);
+    let points: Vec<E::ScalarField> = (0..vector.len()).map(|i| omega.pow([i as u64])).collect();
+    let numerator = interpolate(&points, vector).unwrap();
+
+    // denominator is product of i in indices (X - w^i)
+    let mut denominator = vec![-omega.pow([indices[0] as u64]), E::ScalarField::ONE];
+    for &i in &indices[1..] {
+        denominator = mul(&denominator, &[-omega.pow([i as u64]), E::ScalarField::ONE]);
+    }
+
+    // quotient is numerator divided by denominator, committed to G1
+    let quotient = div(&numerator, &denominator).unwrap();
+    let mut pi = crs_g1[0] * quotient[0];
+    for i in 1..quotient.len() {
+        pi += crs_g1[i] * quotient[i];
+    }
+    
+    Ok(pi)
+}
+
+// verify a subvector commitment
+fn verify_subvector<E: Pairing>(
+    degree: usize,
+    crs_g1: &[E::G1],
+    crs_g2: &[E::G2],
+    commitment: E::G1,
+    indices: &[usize],
+    subvector: &[E::ScalarField],
+    proof: E::G1
+) -> bool {
+    if check_indices(degree, indices).is_err() || indices.len() != subvector.len() {
+        return false;
+    }
+
+    //  denominator is product of i in indices (X - w^i)
+    let omega = get_omega(&vec![E::ScalarField::ZERO; degree]);
+    let mut denominator = vec![-omega.pow([indices[0] as u64]), E::ScalarField::ONE];
+    for &i in &indices[1..] {
+        denominator = mul(&denominator, &[-omega.pow([i as u64]), E::ScalarField::ONE]);
     }
     
+    // commit denominator 
+    let mut denominator_commitment = crs_g2[0].mul(E::ScalarField::ZERO);
+    for i in 0..denominator.len() {
+        denominator_commitment += crs_g2[i] * denominator[i];
+    }
+
+    // remainder is the lagrange interpolation of the subvector over w^i for i in indices
+    let indices_field: Vec<E::ScalarField> = indices.iter().map(|&i| omega.pow([i as u64])).collect();
+    let remainder = interpolate(&indices_field, subvector).unwrap();
+
+    // commit remainder
+    let mut remainder_commitment = crs_g1[0].mul(E::ScalarField::ZERO);
+    for i in 0..remainder.len() {
+        remainder_commitment += crs_g1[i] * remainder[i];
+    }
+
+    // verification 
+    let lhs = E::pairing(proof, denominator_commitment);
+    let rhs = E::pairing(commitment - remainder_commitment, crs_g2[0]);
+    lhs == rhs
+}
+
+// aggregate multiple proofs into one subvector commitment
+fn aggregate_subvector_proofs<E: Pairing>(
+    degree: usize,
+    indices: &[usize],
+    proofs: Vec<E::G1>
+) -> Result<E::G1, &'static str> {
+    check_indices(degree, indices)?;
+    // make sure that length of indices  is the same as the proofs
+    if indices.len() != proofs.len() {
+        return Err("Number of proofs does not match the indices");
+    }
+
+    // A(X) is product of i in indices (X - w^i)
+    let omega = get_omega(&vec![E::ScalarField::ZERO; degree]);
+    let mut a_polynomial = vec![-omega.pow([indices[0] as u64]), E::ScalarField::ONE];
+    for &i in &indices[1..] {
+        a_polynomial = mul(&a_polynomial, &[-omega.pow([i as u64]), E::ScalarField::ONE]);
+    }
+
+    // A'(X), derivative of A(X)
+    let mut a_derivative = vec![E::ScalarField::ZERO; a_polynomial.len() - 1];
+    for i in 1..a_polynomial.len() {
+        a_derivative[i-1] = a_polynomial[i] * E::ScalarField::from(i as u32);
+    }
 
+    // pi is the sum of each proof scaled by 1 / A'(w^i)
+    let pi = indices.iter().enumerate().map(|(k, &i)|{
+        proofs[k].mul(evaluate(&a_derivative, omega.pow([i as u64])).inverse().unwrap())
+    }).sum::<E::G1>();
 
+    Ok(pi)
 }
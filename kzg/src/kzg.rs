@@ -28,12 +28,13 @@ impl <E: Pairing> KZG<E> {
 
     pub fn setup(&mut self, secret: E::ScalarField) {
         for i in 0..self.degree + 1 {
-            self.crs_g1.push(self.g1.mul(secret.pow(&[i as u64])));
-            self.crs_g2.push(self.g2.mul(secret.pow(&[i as u64])));
+            self.crs_g1.push(self.g1.mul(secret.pow([i as u64])));
+            self.crs_g2.push(self.g2.mul(secret.pow([i as u64])));
         }
         self.g2_tau = self.g2.mul(secret);
     }
 
+    #[allow(clippy::needless_range_loop)]
     pub fn commit(&self, poly: &[E::ScalarField]) -> E::G1 {
         let mut commitment = self.g1.mul(E::ScalarField::ZERO);
         for i in 0..self.degree+1 {
@@ -42,6 +43,7 @@ impl <E: Pairing> KZG<E> {
         commitment
     }
 
+    #[allow(clippy::needless_range_loop)]
     pub fn open(&self, poly: &[E::ScalarField], point: E::ScalarField) -> E::G1 {
         // evaluate the polynomial at the point
         let value = evaluate(poly, point);
@@ -67,6 +69,7 @@ impl <E: Pairing> KZG<E> {
         pi
     }
 
+    #[allow(clippy::needless_range_loop)]
     pub fn multi_open(&self, poly: &[E::ScalarField], points: &[E::ScalarField]) -> E::G1 {
         // denominator is a polynomial where all its roots are the points to be evaluated (zero poly)
         // Z(X) = (X - p1)(X - p2)...(X - pn)
@@ -113,6 +116,7 @@ impl <E: Pairing> KZG<E> {
         lhs == rhs
     }
     
+    #[allow(clippy::needless_range_loop)]
     pub fn verify_multi(
         &self, 
         points: &[E::ScalarField],
@@ -132,7 +136,7 @@ impl <E: Pairing> KZG<E> {
         }
 
         // compute the lagrange polynomial
-        let lagrange_poly = interpolate(points, &values).unwrap();
+        let lagrange_poly = interpolate(points, values).unwrap();
 
         // compute the commitment of the lagrange polynomial in regards to crs_g1
         let mut lagrange_commitment = self.g1.mul(E::ScalarField::ZERO);
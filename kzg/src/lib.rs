@@ -1,5 +1,9 @@
 pub mod kzg;
 pub mod asvc;
+pub mod map;
+pub mod sharded;
+pub mod verkle;
+pub mod multilinear;
 pub mod utils;
 
 #[cfg(test)]
@@ -12,6 +16,10 @@ mod tests {
     use ark_bls12_381::{Bls12_381, G1Projective as G1, G2Projective as G2, Fr};
     use kzg::KZG;
     use asvc::ASVC;
+    use map::AuthenticatedMap;
+    use sharded::ShardedVector;
+    use verkle::VerkleTree;
+    use multilinear::MultilinearKZG;
     use utils::evaluate;
 
     #[test]
@@ -70,10 +78,7 @@ mod tests {
         let proof = kzg_instance.multi_open(&poly, &points);
 
         // evaluate and verify the kzg proof
-        let mut values = vec![];
-        for i in 0..points.len() {
-            values.push(evaluate(&poly, points[i]));
-        }
+        let values: Vec<Fr> = points.iter().map(|&point| evaluate(&poly, point)).collect();
         assert!(kzg_instance.verify_multi(&points, &values, commitment, proof));
 
         println!("Multi point evaluation verified");
@@ -95,7 +100,7 @@ mod tests {
         );
 
         // generate a random vector and commit to it
-        let vector = vec![Fr::rand(&mut rng); degree];
+        let vector: Vec<Fr> = (0..degree).map(|_| Fr::rand(&mut rng)).collect();
         let commitment = asvc_instance.vector_commit(&vector);
 
         // randomly select three items in the vector and also record their indices
@@ -108,7 +113,7 @@ mod tests {
         }
 
         // prove positions for these three selected indices
-        let pi = asvc_instance.prove_position(&selected_indices, &vector);
+        let pi = asvc_instance.prove_position(&selected_indices, &vector).unwrap();
 
         // verify the proof
         let mut subvector = vec![];
@@ -137,7 +142,7 @@ mod tests {
         );
 
         // generate a random vector and commit to it
-        let vector = vec![Fr::rand(&mut rng); degree];
+        let vector: Vec<Fr> = (0..degree).map(|_| Fr::rand(&mut rng)).collect();
         let commitment = asvc_instance.vector_commit(&vector);
 
         // randomly select three items in the vector and also record their indices
@@ -150,13 +155,13 @@ mod tests {
         }
 
         // prove positions individually for these three selected indices
-        let pi_0 = asvc_instance.prove_position(&[selected_indices[0]], &vector);
-        let pi_1 = asvc_instance.prove_position(&[selected_indices[1]], &vector);
-        let pi_2 = asvc_instance.prove_position(&[selected_indices[2]], &vector);
+        let pi_0 = asvc_instance.prove_position(&[selected_indices[0]], &vector).unwrap();
+        let pi_1 = asvc_instance.prove_position(&[selected_indices[1]], &vector).unwrap();
+        let pi_2 = asvc_instance.prove_position(&[selected_indices[2]], &vector).unwrap();
         let proofs = vec![pi_0, pi_1, pi_2];
 
         // aggregate the proofs
-        let pi = asvc_instance.aggregate_proof(&selected_indices, proofs);
+        let pi = asvc_instance.aggregate_proof(&selected_indices, proofs).unwrap();
 
         // verify the proof
         let mut subvector = vec![];
@@ -168,4 +173,403 @@ mod tests {
         println!("Vector evaluation verified");
 
     }
+
+    #[test]
+    fn test_map_membership() {
+        let mut rng = ark_std::test_rng();
+        let degree = 32;
+        let slot_width = 8;
+
+        let secret = Fr::rand(&mut rng);
+        let asvc_instance = ASVC::<Bls12_381>::key_gen(
+            G1::rand(&mut rng),
+            G2::rand(&mut rng),
+            degree,
+            secret
+        );
+
+        // insert a few keys, one of them with a value spanning several field elements
+        let mut map = AuthenticatedMap::new(&asvc_instance, slot_width);
+        let keys: Vec<[u8; 32]> = (1..=3u8).map(|i| [i; 32]).collect();
+        let long_value = vec![7u8; 40];
+        map.insert(&keys[0], b"first").unwrap();
+        map.insert(&keys[1], &long_value).unwrap();
+        map.insert(&keys[2], b"third").unwrap();
+        map.insert(&keys[0], b"updated").unwrap();
+
+        // lookups see the latest value of each key
+        assert_eq!(map.get(&keys[0]), Some(b"updated".to_vec()));
+        assert_eq!(map.get(&keys[1]), Some(long_value.clone()));
+
+        let (value, proof) = map.get_with_proof(&keys[1]);
+        assert_eq!(value, Some(long_value.clone()));
+        assert!(AuthenticatedMap::verify(&asvc_instance, slot_width, map.commitment(), &keys[1], Some(&long_value), &proof));
+
+        // a wrong value or a claimed absence does not verify
+        assert!(!AuthenticatedMap::verify(&asvc_instance, slot_width, map.commitment(), &keys[1], Some(b"forged"), &proof));
+        assert!(!AuthenticatedMap::verify(&asvc_instance, slot_width, map.commitment(), &keys[1], None, &proof));
+
+        println!("Map membership verified");
+    }
+
+    #[test]
+    fn test_map_non_membership_and_batch() {
+        let mut rng = ark_std::test_rng();
+        let degree = 32;
+        let slot_width = 8;
+
+        let secret = Fr::rand(&mut rng);
+        let asvc_instance = ASVC::<Bls12_381>::key_gen(
+            G1::rand(&mut rng),
+            G2::rand(&mut rng),
+            degree,
+            secret
+        );
+
+        // fill three of the four slots so that probing has to walk past collisions
+        let mut map = AuthenticatedMap::new(&asvc_instance, slot_width);
+        let keys: Vec<[u8; 32]> = (1..=3u8).map(|i| [i; 32]).collect();
+        for key in &keys {
+            map.insert(key, key).unwrap();
+        }
+
+        // prove that an unknown key is absent
+        let missing = [42u8; 32];
+        let (value, proof) = map.get_with_proof(&missing);
+        assert_eq!(value, None);
+        assert!(AuthenticatedMap::verify(&asvc_instance, slot_width, map.commitment(), &missing, None, &proof));
+        assert!(!AuthenticatedMap::verify(&asvc_instance, slot_width, map.commitment(), &missing, Some(&missing), &proof));
+
+        // batch lookup of present and absent keys under one proof
+        let lookup = vec![keys[0], missing, keys[2]];
+        let (values, proof) = map.batch_get_with_proof(&lookup);
+        let values: Vec<Option<&[u8]>> = values.iter().map(|v| v.as_deref()).collect();
+        assert_eq!(values, vec![Some(&keys[0][..]), None, Some(&keys[2][..])]);
+        assert!(AuthenticatedMap::batch_verify(&asvc_instance, slot_width, map.commitment(), &lookup, &values, &proof));
+
+        // an empty batch has an empty proof
+        let (values, proof) = map.batch_get_with_proof(&[]);
+        assert!(values.is_empty() && proof.openings.is_empty());
+        assert!(AuthenticatedMap::batch_verify(&asvc_instance, slot_width, map.commitment(), &[], &[], &proof));
+
+        // a full map rejects new keys
+        map.insert(&missing, b"fourth").unwrap();
+        assert!(map.insert(&[43u8; 32], b"fifth").is_err());
+
+        println!("Map non-membership verified");
+    }
+
+    #[test]
+    fn test_sharded_vector() {
+        let mut rng = ark_std::test_rng();
+        let degree = 8;
+
+        let secret = Fr::rand(&mut rng);
+        let asvc_instance = ASVC::<Bls12_381>::key_gen(
+            G1::rand(&mut rng),
+            G2::rand(&mut rng),
+            degree,
+            secret
+        );
+
+        // commit to a vector larger than the CRS, then keep appending to it
+        let vector: Vec<Fr> = (0..20).map(|_| Fr::rand(&mut rng)).collect();
+        let mut sharded = ShardedVector::from_vector(&asvc_instance, &vector[..13]).unwrap();
+        let before = sharded.commitment();
+        sharded.extend(&vector[13..]).unwrap();
+        assert_ne!(before, sharded.commitment());
+        assert_eq!(sharded.len(), 20);
+
+        // appending element by element gives the same commitment as building at once
+        let rebuilt = ShardedVector::from_vector(&asvc_instance, &vector).unwrap();
+        assert_eq!(rebuilt.commitment(), sharded.commitment());
+
+        // prove a subvector spanning all three shards
+        let indices = vec![1, 6, 9, 17, 19];
+        let values: Vec<Fr> = indices.iter().map(|&i| vector[i]).collect();
+        let proof = sharded.prove_positions(&indices);
+        assert!(ShardedVector::verify_positions(&asvc_instance, sharded.commitment(), &indices, &values, &proof));
+
+        // wrong values and out of bounds indices are rejected
+        let mut wrong = values.clone();
+        wrong[3] += Fr::from(1u64);
+        assert!(!ShardedVector::verify_positions(&asvc_instance, sharded.commitment(), &indices, &wrong, &proof));
+        assert!(!ShardedVector::verify_positions(&asvc_instance, before, &indices, &values, &proof));
+        assert!(!ShardedVector::verify_positions(&asvc_instance, sharded.commitment(), &[1, 6, 9, 17, 20], &values, &proof));
+
+        // the vector is full at degree * (degree - 1) values
+        assert_eq!(sharded.capacity(), 56);
+        let more: Vec<Fr> = (20..56).map(|i| Fr::from(i as u64)).collect();
+        sharded.extend(&more).unwrap();
+        assert_eq!(sharded.append(Fr::from(56u64)), Err("Vector is full"));
+        assert_eq!(sharded.len(), 56);
+        let too_long: Vec<Fr> = (0..57).map(|i| Fr::from(i as u64)).collect();
+        assert!(ShardedVector::from_vector(&asvc_instance, &too_long).is_err());
+
+        println!("Sharded vector verified");
+    }
+
+    // size in bytes of a binary Merkle multiproof over 32-byte hashes, counting each
+    // sibling once and leaving out nodes the verifier can recompute
+    fn merkle_multiproof_size(num_leaves: usize, positions: &[usize]) -> usize {
+        let mut known: std::collections::BTreeSet<usize> = positions.iter().cloned().collect();
+        let mut siblings = 0;
+        let mut width = num_leaves.next_power_of_two();
+        while width > 1 {
+            siblings += known.iter().filter(|&&i| !known.contains(&(i ^ 1))).count();
+            known = known.iter().map(|&i| i / 2).collect();
+            width /= 2;
+        }
+        siblings * 32
+    }
+
+    #[test]
+    fn test_verkle_tree() {
+        use ark_serialize::{CanonicalSerialize, CanonicalDeserialize};
+        use rand::RngCore;
+
+        let mut rng = ark_std::test_rng();
+        let degree = 16;
+
+        let secret = Fr::rand(&mut rng);
+        let asvc_instance = ASVC::<Bls12_381>::key_gen(
+            G1::rand(&mut rng),
+            G2::rand(&mut rng),
+            degree,
+            secret
+        );
+
+        // insert random keys, update one of them and delete another
+        let mut tree = VerkleTree::new(&asvc_instance);
+        let keys: Vec<[u8; 32]> = (0..256).map(|_| {
+            let mut key = [0u8; 32];
+            rng.fill_bytes(&mut key);
+            key
+        }).collect();
+        for key in &keys {
+            assert_eq!(tree.insert(key, &key[..4]), None);
+        }
+        assert_eq!(tree.insert(&keys[0], b"updated"), Some(keys[0][..4].to_vec()));
+        assert_eq!(tree.remove(&keys[1]), Some(keys[1][..4].to_vec()));
+        assert_eq!(tree.get(&keys[1]), None);
+
+        // the trie shape does not depend on the order of operations
+        let mut rebuilt = VerkleTree::new(&asvc_instance);
+        for key in keys[2..].iter().rev() {
+            rebuilt.insert(key, &key[..4]);
+        }
+        rebuilt.insert(&keys[0], b"updated");
+        assert_eq!(rebuilt.commitment(), tree.commitment());
+
+        // prove present and missing keys with one multiproof
+        let mut missing = [0u8; 32];
+        rng.fill_bytes(&mut missing);
+        let positions = [0, 1, 5, 9, 20, 33, 63, 100, 128, 150, 199, 255];
+        let mut lookup: Vec<[u8; 32]> = positions.iter().map(|&i| keys[i]).collect();
+        lookup.insert(2, missing);
+        let (values, proof) = tree.prove(&lookup);
+        let values: Vec<Option<&[u8]>> = values.iter().map(|v| v.as_deref()).collect();
+        assert_eq!(values[0], Some(&b"updated"[..]));
+        assert_eq!(values[1], None);
+        assert_eq!(values[2], None);
+        assert!(VerkleTree::verify(&asvc_instance, tree.commitment(), &lookup, &values, &proof));
+
+        // the proof survives serialization
+        let mut bytes = Vec::new();
+        proof.serialize_compressed(&mut bytes).unwrap();
+        let decoded = verkle::VerkleProof::<Bls12_381>::deserialize_compressed(&bytes[..]).unwrap();
+        assert!(VerkleTree::verify(&asvc_instance, tree.commitment(), &lookup, &values, &decoded));
+
+        // tampered values and stale roots are rejected
+        let mut forged = values.clone();
+        forged[4] = Some(&b"forged"[..]);
+        assert!(!VerkleTree::verify(&asvc_instance, tree.commitment(), &lookup, &forged, &proof));
+        let mut forged = values.clone();
+        forged[1] = Some(&keys[1][..4]);
+        assert!(!VerkleTree::verify(&asvc_instance, tree.commitment(), &lookup, &forged, &proof));
+        assert!(!VerkleTree::verify(&asvc_instance, rebuilt.commitment() + G1::rand(&mut rng), &lookup, &values, &proof));
+
+        // the multiproof is smaller than a Merkle multiproof over the same keys
+        let merkle_size = merkle_multiproof_size(keys.len(), &positions);
+        println!("Verkle proof: {} bytes, Merkle proof: {} bytes", bytes.len(), merkle_size);
+        assert!(bytes.len() < merkle_size);
+    }
+
+    #[test]
+    fn test_verkle_digests_are_domain_separated() {
+        use ark_serialize::CanonicalSerialize;
+
+        // an encoding that reads as the leaf tag, a key and a value, as a commitment
+        // encoding may on a curve with another point format
+        let mut encoding = vec![1u8];
+        encoding.extend_from_slice(&[7u8; 32]);
+        encoding.extend_from_slice(&[9u8; 15]);
+        let key: [u8; 32] = encoding[1..33].try_into().unwrap();
+        let leaf: Fr = verkle::leaf_digest(&key, &encoding[33..]);
+
+        // hashing the raw encoding would give the leaf digest, the tagged one does not
+        assert_eq!(leaf, utils::hash_to_field::<Fr>(&encoding));
+        assert_ne!(leaf, utils::tagged_hash_to_field::<Fr>(utils::COMMITMENT_TAG, &encoding));
+
+        // and commitments are hashed behind their tag
+        let commitment = G1::rand(&mut ark_std::test_rng());
+        let mut bytes = vec![];
+        commitment.serialize_compressed(&mut bytes).unwrap();
+        assert_eq!(
+            utils::commitment_to_field::<Bls12_381>(&commitment),
+            utils::tagged_hash_to_field::<Fr>(utils::COMMITMENT_TAG, &bytes)
+        );
+    }
+
+    #[test]
+    fn test_prover_verifier_roles() {
+        use ark_serialize::CanonicalSerialize;
+
+        let mut rng = ark_std::test_rng();
+        let degree = 16;
+
+        let secret = Fr::rand(&mut rng);
+        let asvc_instance = ASVC::<Bls12_381>::key_gen(
+            G1::rand(&mut rng),
+            G2::rand(&mut rng),
+            degree,
+            secret
+        );
+
+        // split the keys, giving the client a verifier for up to three positions
+        let prover = asvc_instance.prover();
+        let verifier = asvc_instance.verifier(3);
+        let mut verifier_bytes = Vec::new();
+        verifier.serialize_compressed(&mut verifier_bytes).unwrap();
+        assert!(verifier_bytes.len() < 1024);
+
+        let vector: Vec<Fr> = (0..degree).map(|_| Fr::rand(&mut rng)).collect();
+        let commitment = prover.vector_commit(&vector);
+
+        // a subvector proof and an aggregated proof both verify with the small key
+        let indices = vec![2, 7, 11];
+        let subvector: Vec<Fr> = indices.iter().map(|&i| vector[i]).collect();
+        let pi = prover.prove_position(&indices, &vector).unwrap();
+        assert!(verifier.verify_position(commitment, &indices, &subvector, pi));
+
+        let proofs = indices.iter().map(|&i| prover.prove_position(&[i], &vector).unwrap()).collect();
+        let pi = prover.aggregate_proof(&indices, proofs).unwrap();
+        assert!(verifier.verify_position(commitment, &indices, &subvector, pi));
+
+        // wrong values and subvectors larger than the key are rejected
+        let mut wrong = subvector.clone();
+        wrong[0] += Fr::from(1u64);
+        assert!(!verifier.verify_position(commitment, &indices, &wrong, pi));
+
+        let indices = vec![1, 2, 3, 4];
+        let subvector: Vec<Fr> = indices.iter().map(|&i| vector[i]).collect();
+        let pi = prover.prove_position(&indices, &vector).unwrap();
+        assert!(asvc_instance.verify_positon(commitment, &indices, &subvector, pi));
+        assert!(!verifier.verify_position(commitment, &indices, &subvector, pi));
+
+        println!("Verifier key of {} bytes verified", verifier_bytes.len());
+    }
+
+    #[test]
+    fn test_subvector_index_checks() {
+        let mut rng = ark_std::test_rng();
+        let degree = 8;
+
+        let secret = Fr::rand(&mut rng);
+        let asvc_instance = ASVC::<Bls12_381>::key_gen(
+            G1::rand(&mut rng),
+            G2::rand(&mut rng),
+            degree,
+            secret
+        );
+        let prover = asvc_instance.prover();
+        let verifier = asvc_instance.verifier(3);
+        let vector: Vec<Fr> = (0..degree).map(|_| Fr::rand(&mut rng)).collect();
+        let commitment = prover.vector_commit(&vector);
+        let pi = prover.prove_position(&[2, 5], &vector).unwrap();
+        let subvector = [vector[2], vector[5]];
+        assert!(verifier.verify_position(commitment, &[2, 5], &subvector, pi));
+
+        // an empty index list
+        assert_eq!(prover.prove_position(&[], &vector), Err("No indices given"));
+        assert_eq!(prover.aggregate_proof(&[], vec![]), Err("No indices given"));
+        assert!(!verifier.verify_position(commitment, &[], &[], pi));
+
+        // an index past the degree, which would alias index 2 through w^i
+        assert_eq!(prover.prove_position(&[degree + 2, 5], &vector), Err("Index out of range"));
+        assert!(!verifier.verify_position(commitment, &[degree + 2, 5], &subvector, pi));
+        assert!(!asvc_instance.verify_positon(commitment, &[degree + 2, 5], &subvector, pi));
+
+        // a repeated index
+        assert_eq!(prover.prove_position(&[2, 2], &vector), Err("Duplicate index"));
+        assert_eq!(prover.aggregate_proof(&[2, 2], vec![pi, pi]), Err("Duplicate index"));
+        assert!(!verifier.verify_position(commitment, &[2, 2], &[vector[2], vector[2]], pi));
+    }
+
+    #[test]
+    fn test_verify_keys() {
+        let mut rng = ark_std::test_rng();
+        let degree = 8;
+
+        let secret = Fr::rand(&mut rng);
+        let mut asvc_instance = ASVC::<Bls12_381>::key_gen(
+            G1::rand(&mut rng),
+            G2::rand(&mut rng),
+            degree,
+            secret
+        );
+        assert_eq!(asvc_instance.verify_keys(&mut rng), Ok(()));
+
+        // tampering with any part of the published key is detected
+        let original = asvc_instance.proving_key.li_commitment[3];
+        asvc_instance.proving_key.li_commitment[3] += G1::rand(&mut rng);
+        assert!(asvc_instance.verify_keys(&mut rng).is_err());
+        asvc_instance.proving_key.li_commitment[3] = original;
+
+        let original = asvc_instance.update_key.ui_commitment[5];
+        asvc_instance.update_key.ui_commitment[5] += G1::rand(&mut rng);
+        asvc_instance.proving_key.update_key.ui_commitment[5] = asvc_instance.update_key.ui_commitment[5];
+        assert!(asvc_instance.verify_keys(&mut rng).is_err());
+        asvc_instance.update_key.ui_commitment[5] = original;
+        asvc_instance.proving_key.update_key.ui_commitment[5] = original;
+
+        let original = asvc_instance.verification_key.crs.g2[4];
+        asvc_instance.verification_key.crs.g2[4] += G2::rand(&mut rng);
+        asvc_instance.proving_key.crs.g2[4] = asvc_instance.verification_key.crs.g2[4];
+        assert!(asvc_instance.verify_keys(&mut rng).is_err());
+        asvc_instance.verification_key.crs.g2[4] = original;
+        asvc_instance.proving_key.crs.g2[4] = original;
+
+        assert_eq!(asvc_instance.verify_keys(&mut rng), Ok(()));
+
+        println!("Key generation output verified");
+    }
+
+    #[test]
+    fn test_multilinear_evaluation() {
+        use ark_poly::{DenseMultilinearExtension, MultilinearExtension};
+
+        let mut rng = ark_std::test_rng();
+        let num_vars = 6;
+
+        // initialize a multilinear KZG instance and run the trusted setup
+        let mut mlkzg = MultilinearKZG::<Bls12_381>::new(G1::rand(&mut rng), G2::rand(&mut rng), num_vars);
+        let secrets: Vec<Fr> = (0..num_vars).map(|_| Fr::rand(&mut rng)).collect();
+        mlkzg.setup(&secrets);
+
+        // commit to a random multilinear polynomial and open it at a random point
+        let polynomial = DenseMultilinearExtension::<Fr>::rand(num_vars, &mut rng);
+        let commitment = mlkzg.commit(&polynomial.evaluations);
+        let point: Vec<Fr> = (0..num_vars).map(|_| Fr::rand(&mut rng)).collect();
+        let proof = mlkzg.open(&polynomial.evaluations, &point);
+
+        let value = polynomial.evaluate(&point).unwrap();
+        assert!(mlkzg.verify(&point, value, commitment, &proof));
+        assert!(!mlkzg.verify(&point, value + Fr::from(1u64), commitment, &proof));
+
+        // the opening is bound to the point
+        let mut other_point = point.clone();
+        other_point[2] += Fr::from(1u64);
+        assert!(!mlkzg.verify(&other_point, value, commitment, &proof));
+    }
 }
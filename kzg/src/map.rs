@@ -0,0 +1,317 @@
+//! Authenticated key-value map on top of ASVC.
+//!
+//! The committed vector is split into slots of `slot_width` elements. Each slot holds
+//! a header (occupied flag, the two halves of the key and the value length in bytes)
+//! followed by the value, packed into field elements. Keys are hashed to a starting
+//! slot and collisions are resolved with linear probing.
+
+use std::collections::BTreeMap;
+use ark_ff::{Field, PrimeField, BigInteger, Zero};
+use ark_ec::pairing::Pairing;
+use sha2::{Digest, Sha256};
+use crate::asvc::ASVC;
+
+pub type Key = [u8; 32];
+
+/// number of header elements at the start of each slot
+pub const HEADER_LENGTH: usize = 4;
+
+/// opened elements of the slots visited while looking up one key
+#[derive(Clone)]
+pub struct LookupOpening<F: Field> {
+    /// headers of the probed slots, in probe order
+    pub headers: Vec<[F; HEADER_LENGTH]>,
+    /// value elements of the last probed slot, empty for non-membership
+    pub value: Vec<F>,
+}
+
+#[derive(Clone)]
+pub struct MapProof<E: Pairing> {
+    /// one opening per looked up key
+    pub openings: Vec<LookupOpening<E::ScalarField>>,
+    /// single ASVC proof for all opened positions
+    pub proof: E::G1,
+}
+
+pub struct AuthenticatedMap<'a, E: Pairing> {
+    asvc: &'a ASVC<E>,
+    slot_width: usize,
+    vector: Vec<E::ScalarField>,
+    commitment: E::G1,
+}
+
+impl<'a, E: Pairing> AuthenticatedMap<'a, E> {
+    pub fn new(asvc: &'a ASVC<E>, slot_width: usize) -> Self {
+        assert!(slot_width > HEADER_LENGTH, "slot must have room for a value");
+        assert!(asvc.degree >= slot_width, "vector must hold at least one slot");
+
+        let vector = vec![E::ScalarField::ZERO; asvc.degree];
+        let commitment = asvc.vector_commit(&vector);
+        Self {
+            asvc,
+            slot_width,
+            vector,
+            commitment,
+        }
+    }
+
+    pub fn commitment(&self) -> E::G1 {
+        self.commitment
+    }
+
+    pub fn num_slots(&self) -> usize {
+        self.asvc.degree / self.slot_width
+    }
+
+    // maximum value length in bytes that fits into a slot
+    pub fn max_value_length(&self) -> usize {
+        (self.slot_width - HEADER_LENGTH) * chunk_size::<E::ScalarField>()
+    }
+
+    // insert a key or overwrite its value, updating the commitment in place
+    pub fn insert(&mut self, key: &Key, value: &[u8]) -> Result<(), &'static str> {
+        if value.len() > self.max_value_length() {
+            return Err("Value does not fit into a slot");
+        }
+        let slot = match self.find_slot(key) {
+            (slot, _) if slot < self.num_slots() => slot,
+            _ => return Err("Map is full"),
+        };
+
+        // write the new slot content and update the commitment with the deltas
+        let (key_lo, key_hi) = encode_key::<E::ScalarField>(key);
+        let mut content = vec![
+            E::ScalarField::ONE,
+            key_lo,
+            key_hi,
+            E::ScalarField::from(value.len() as u64),
+        ];
+        content.extend(encode_value::<E::ScalarField>(value));
+        content.resize(self.slot_width, E::ScalarField::ZERO);
+
+        for (offset, element) in content.into_iter().enumerate() {
+            let index = slot * self.slot_width + offset;
+            let delta = element - self.vector[index];
+            if !delta.is_zero() {
+                self.commitment = self.asvc.update_commitment(self.commitment, index, delta);
+                self.vector[index] = element;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn get(&self, key: &Key) -> Option<Vec<u8>> {
+        let (slot, found) = self.find_slot(key);
+        if found {
+            Some(self.read_value(slot))
+        } else {
+            None
+        }
+    }
+
+    // look up a key, proving either its value or that it is absent
+    pub fn get_with_proof(&self, key: &Key) -> (Option<Vec<u8>>, MapProof<E>) {
+        let (mut values, proof) = self.batch_get_with_proof(&[*key]);
+        (values.pop().unwrap(), proof)
+    }
+
+    // look up several keys with a single aggregated proof
+    pub fn batch_get_with_proof(&self, keys: &[Key]) -> (Vec<Option<Vec<u8>>>, MapProof<E>) {
+        let mut values = Vec::with_capacity(keys.len());
+        let mut openings = Vec::with_capacity(keys.len());
+        let mut positions = BTreeMap::new();
+
+        for key in keys {
+            let (slot, found) = self.find_slot(key);
+            let probed = probe_sequence(key, self.num_slots())
+                .take_while(|&s| s != slot)
+                .chain(std::iter::once(slot).filter(|&s| s < self.num_slots()));
+
+            let mut headers = vec![];
+            for s in probed {
+                let start = s * self.slot_width;
+                let mut header = [E::ScalarField::ZERO; HEADER_LENGTH];
+                header.copy_from_slice(&self.vector[start..start + HEADER_LENGTH]);
+                for index in start..start + HEADER_LENGTH {
+                    positions.insert(index, self.vector[index]);
+                }
+                headers.push(header);
+            }
+
+            let mut value = vec![];
+            if found {
+                let bytes = self.read_value(slot);
+                let start = slot * self.slot_width + HEADER_LENGTH;
+                for index in start..start + num_chunks::<E::ScalarField>(bytes.len()) {
+                    positions.insert(index, self.vector[index]);
+                    value.push(self.vector[index]);
+                }
+                values.push(Some(bytes));
+            } else {
+                values.push(None);
+            }
+            openings.push(LookupOpening { headers, value });
+        }
+
+        // an empty lookup opens nothing
+        if positions.is_empty() {
+            return (values, MapProof { openings, proof: E::G1::zero() });
+        }
+        let indices: Vec<usize> = positions.keys().cloned().collect();
+        let proof = self.asvc.prove_position(&indices, &self.vector)
+            .expect("opened positions are distinct slots of the vector");
+        (values, MapProof { openings, proof })
+    }
+
+    // verify a lookup of a single key, where a value of None claims non-membership
+    pub fn verify(
+        asvc: &ASVC<E>,
+        slot_width: usize,
+        commitment: E::G1,
+        key: &Key,
+        value: Option<&[u8]>,
+        proof: &MapProof<E>,
+    ) -> bool {
+        Self::batch_verify(asvc, slot_width, commitment, &[*key], &[value], proof)
+    }
+
+    // verify a batch lookup produced by batch_get_with_proof
+    pub fn batch_verify(
+        asvc: &ASVC<E>,
+        slot_width: usize,
+        commitment: E::G1,
+        keys: &[Key],
+        values: &[Option<&[u8]>],
+        proof: &MapProof<E>,
+    ) -> bool {
+        if keys.len() != values.len() || keys.len() != proof.openings.len() {
+            return false;
+        }
+        if keys.is_empty() {
+            return true;
+        }
+        let num_slots = asvc.degree / slot_width;
+        let mut positions = BTreeMap::new();
+
+        for ((key, value), opening) in keys.iter().zip(values).zip(&proof.openings) {
+            let (key_lo, key_hi) = encode_key::<E::ScalarField>(key);
+            let probed: Vec<usize> = probe_sequence(key, num_slots).collect();
+            if opening.headers.is_empty() || opening.headers.len() > probed.len() {
+                return false;
+            }
+
+            // every probed slot but the last must hold a different key
+            let (last, rest) = opening.headers.split_last().unwrap();
+            for header in rest {
+                if header[0] != E::ScalarField::ONE || (header[1] == key_lo && header[2] == key_hi) {
+                    return false;
+                }
+            }
+
+            match value {
+                Some(bytes) => {
+                    let expected = encode_value::<E::ScalarField>(bytes);
+                    if last[0] != E::ScalarField::ONE
+                        || last[1] != key_lo
+                        || last[2] != key_hi
+                        || last[3] != E::ScalarField::from(bytes.len() as u64)
+                        || opening.value != expected
+                    {
+                        return false;
+                    }
+                }
+                None => {
+                    // the probe ends at an empty slot, or the whole map was probed
+                    let is_empty = last[0] == E::ScalarField::ZERO;
+                    let is_other = last[0] == E::ScalarField::ONE && (last[1] != key_lo || last[2] != key_hi);
+                    let exhausted = opening.headers.len() == probed.len();
+                    if !(is_empty || (is_other && exhausted)) || !opening.value.is_empty() {
+                        return false;
+                    }
+                }
+            }
+
+            // collect the opened positions, rejecting conflicting duplicates
+            let last_slot = probed[opening.headers.len() - 1];
+            let mut opened: Vec<(usize, E::ScalarField)> = vec![];
+            for (&slot, header) in probed.iter().zip(&opening.headers) {
+                for (offset, &element) in header.iter().enumerate() {
+                    opened.push((slot * slot_width + offset, element));
+                }
+            }
+            for (offset, &element) in opening.value.iter().enumerate() {
+                opened.push((last_slot * slot_width + HEADER_LENGTH + offset, element));
+            }
+            for (index, element) in opened {
+                if *positions.entry(index).or_insert(element) != element {
+                    return false;
+                }
+            }
+        }
+
+        let indices: Vec<usize> = positions.keys().cloned().collect();
+        let subvector: Vec<E::ScalarField> = positions.values().cloned().collect();
+        asvc.verify_positon(commitment, &indices, &subvector, proof.proof)
+    }
+
+    // follow the probe sequence of a key, returning the slot holding it or the first
+    // empty slot; returns num_slots if every slot is taken by another key
+    fn find_slot(&self, key: &Key) -> (usize, bool) {
+        let (key_lo, key_hi) = encode_key::<E::ScalarField>(key);
+        for slot in probe_sequence(key, self.num_slots()) {
+            let start = slot * self.slot_width;
+            if self.vector[start].is_zero() {
+                return (slot, false);
+            }
+            if self.vector[start + 1] == key_lo && self.vector[start + 2] == key_hi {
+                return (slot, true);
+            }
+        }
+        (self.num_slots(), false)
+    }
+
+    fn read_value(&self, slot: usize) -> Vec<u8> {
+        let start = slot * self.slot_width;
+        let length = self.vector[start + 3].into_bigint().as_ref()[0] as usize;
+        let chunks = &self.vector[start + HEADER_LENGTH..start + HEADER_LENGTH + num_chunks::<E::ScalarField>(length)];
+        decode_value(chunks, length)
+    }
+}
+
+// slots visited for a key, starting from its hash and wrapping around once
+fn probe_sequence(key: &Key, num_slots: usize) -> impl Iterator<Item = usize> {
+    let digest = Sha256::digest(key);
+    let mut start = [0u8; 8];
+    start.copy_from_slice(&digest[..8]);
+    let start = (u64::from_le_bytes(start) % num_slots as u64) as usize;
+    (0..num_slots).map(move |i| (start + i) % num_slots)
+}
+
+// number of value bytes packed into each field element
+fn chunk_size<F: PrimeField>() -> usize {
+    (F::MODULUS_BIT_SIZE as usize - 1) / 8
+}
+
+fn num_chunks<F: PrimeField>(length: usize) -> usize {
+    length.div_ceil(chunk_size::<F>())
+}
+
+// split a key into two 128-bit halves so that the encoding is injective
+fn encode_key<F: PrimeField>(key: &Key) -> (F, F) {
+    (F::from_le_bytes_mod_order(&key[..16]), F::from_le_bytes_mod_order(&key[16..]))
+}
+
+fn encode_value<F: PrimeField>(value: &[u8]) -> Vec<F> {
+    value.chunks(chunk_size::<F>()).map(F::from_le_bytes_mod_order).collect()
+}
+
+fn decode_value<F: PrimeField>(chunks: &[F], length: usize) -> Vec<u8> {
+    let mut bytes = vec![];
+    for chunk in chunks {
+        let mut chunk_bytes = chunk.into_bigint().to_bytes_le();
+        chunk_bytes.truncate(chunk_size::<F>());
+        bytes.extend(chunk_bytes);
+    }
+    bytes.truncate(length);
+    bytes
+}
@@ -0,0 +1,101 @@
+//! Multilinear KZG commitments (Papamanthou, Shi and Tamassia, 2013).
+//!
+//! A multilinear polynomial in `num_vars` variables is given by its evaluations over
+//! the boolean hypercube, bit i of an index giving variable i. Opening at a point z
+//! uses f(X) - f(z) = sum_i (X_i - z_i) q_i(X_{i+1}, ..., X_n), committing to each
+//! quotient q_i with the Lagrange basis of the variables it depends on.
+
+use std::ops::Mul;
+use ark_ff::Field;
+use ark_ec::pairing::Pairing;
+
+pub struct MultilinearKZG<E: Pairing> {
+    pub g1: E::G1,
+    pub g2: E::G2,
+    pub num_vars: usize,
+    /// basis eq(b, tau_i, ..., tau_n) in G1 for the variables i..n, for each i
+    pub crs_g1: Vec<Vec<E::G1>>,
+    /// tau_i in G2 for each variable
+    pub crs_g2: Vec<E::G2>,
+}
+
+impl<E: Pairing> MultilinearKZG<E> {
+    pub fn new(g1: E::G1, g2: E::G2, num_vars: usize) -> Self {
+        Self {
+            g1,
+            g2,
+            num_vars,
+            crs_g1: vec![],
+            crs_g2: vec![],
+        }
+    }
+
+    pub fn setup(&mut self, secrets: &[E::ScalarField]) {
+        assert_eq!(secrets.len(), self.num_vars, "one secret per variable");
+        self.crs_g1 = (0..=self.num_vars)
+            .map(|i| eq_table(&secrets[i..]).into_iter().map(|eq| self.g1.mul(eq)).collect())
+            .collect();
+        self.crs_g2 = secrets.iter().map(|&secret| self.g2.mul(secret)).collect();
+    }
+
+    pub fn commit(&self, evaluations: &[E::ScalarField]) -> E::G1 {
+        assert_eq!(evaluations.len(), 1 << self.num_vars, "wrong number of evaluations");
+        msm::<E>(&self.crs_g1[0], evaluations)
+    }
+
+    // one quotient commitment per variable
+    pub fn open(&self, evaluations: &[E::ScalarField], point: &[E::ScalarField]) -> Vec<E::G1> {
+        assert_eq!(evaluations.len(), 1 << self.num_vars, "wrong number of evaluations");
+        assert_eq!(point.len(), self.num_vars, "wrong number of coordinates");
+
+        let mut table = evaluations.to_vec();
+        let mut proof = Vec::with_capacity(self.num_vars);
+        for (i, &z) in point.iter().enumerate() {
+            // q_i is the slope in variable i, then variable i is fixed to z_i
+            let half = table.len() / 2;
+            let quotient: Vec<E::ScalarField> = (0..half).map(|b| table[2 * b + 1] - table[2 * b]).collect();
+            proof.push(msm::<E>(&self.crs_g1[i + 1], &quotient));
+            table = (0..half).map(|b| table[2 * b] + z * quotient[b]).collect();
+        }
+        proof
+    }
+
+    pub fn verify(
+        &self,
+        point: &[E::ScalarField],
+        value: E::ScalarField,
+        commitment: E::G1,
+        proof: &[E::G1]
+    ) -> bool {
+        if point.len() != self.num_vars || proof.len() != self.num_vars {
+            return false;
+        }
+        // e(C - g^v, h) = prod_i e(pi_i, h^(tau_i - z_i))
+        let lhs = E::pairing(commitment - self.g1.mul(value), self.g2);
+        let rhs = E::multi_pairing(
+            proof.iter().cloned(),
+            self.crs_g2.iter().zip(point).map(|(&tau, &z)| tau - self.g2.mul(z)),
+        );
+        lhs == rhs
+    }
+}
+
+// eq(b, point) for every b in the hypercube, bit j of b giving coordinate j
+fn eq_table<F: Field>(point: &[F]) -> Vec<F> {
+    let mut table = vec![F::ONE];
+    for &r in point {
+        let mut next = Vec::with_capacity(2 * table.len());
+        next.extend(table.iter().map(|&value| value * (F::ONE - r)));
+        next.extend(table.iter().map(|&value| value * r));
+        table = next;
+    }
+    table
+}
+
+fn msm<E: Pairing>(bases: &[E::G1], scalars: &[E::ScalarField]) -> E::G1 {
+    let mut result = E::G1::default();
+    for (&base, &scalar) in bases.iter().zip(scalars) {
+        result += base * scalar;
+    }
+    result
+}
@@ -0,0 +1,191 @@
+//! Growable vector commitment sharded over several ASVC commitments.
+//!
+//! The vector is split into shards of `degree` entries, each committed with ASVC.
+//! A top-level ASVC commitment holds the vector length at position 0 and the digest
+//! of shard `s` at position `s + 1`, so the vector may hold up to `degree * (degree - 1)`
+//! entries with a single CRS; appending past that returns an error.
+
+use std::collections::BTreeMap;
+use ark_ff::{Field, Zero};
+use ark_ec::pairing::Pairing;
+use crate::asvc::ASVC;
+use crate::utils::commitment_to_field;
+
+#[derive(Clone)]
+pub struct ShardedProof<E: Pairing> {
+    /// length of the committed vector, opened from the top-level commitment
+    pub len: usize,
+    /// commitments of the touched shards, ordered by shard index
+    pub shard_commitments: Vec<E::G1>,
+    /// one subvector proof per touched shard
+    pub shard_proofs: Vec<E::G1>,
+    /// proof for the length and the touched shard digests in the top-level commitment
+    pub top_proof: E::G1,
+}
+
+pub struct ShardedVector<'a, E: Pairing> {
+    asvc: &'a ASVC<E>,
+    shards: Vec<Vec<E::ScalarField>>,
+    shard_commitments: Vec<E::G1>,
+    top: Vec<E::ScalarField>,
+    root: E::G1,
+    len: usize,
+}
+
+impl<'a, E: Pairing> ShardedVector<'a, E> {
+    pub fn new(asvc: &'a ASVC<E>) -> Self {
+        // the all-zero top level commits to the identity
+        Self {
+            asvc,
+            shards: vec![],
+            shard_commitments: vec![],
+            top: vec![E::ScalarField::ZERO; asvc.degree],
+            root: E::G1::zero(),
+            len: 0,
+        }
+    }
+
+    pub fn from_vector(asvc: &'a ASVC<E>, vector: &[E::ScalarField]) -> Result<Self, &'static str> {
+        let mut sharded = Self::new(asvc);
+        sharded.extend(vector)?;
+        Ok(sharded)
+    }
+
+    // top-level commitment to the whole vector, including its length
+    pub fn commitment(&self) -> E::G1 {
+        self.root
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    // largest length, one shard per top-level position after the length
+    pub fn capacity(&self) -> usize {
+        self.asvc.degree * (self.asvc.degree - 1)
+    }
+
+    pub fn get(&self, index: usize) -> Option<E::ScalarField> {
+        if index < self.len {
+            Some(self.shards[index / self.asvc.degree][index % self.asvc.degree])
+        } else {
+            None
+        }
+    }
+
+    // append one value, opening a new shard when the last one is full; fails once the
+    // vector holds capacity() values
+    pub fn append(&mut self, value: E::ScalarField) -> Result<(), &'static str> {
+        if self.len == self.capacity() {
+            return Err("Vector is full");
+        }
+        let degree = self.asvc.degree;
+        let (shard, offset) = (self.len / degree, self.len % degree);
+        if offset == 0 {
+            self.shard_commitments.push(E::G1::zero());
+            self.shards.push(vec![E::ScalarField::ZERO; degree]);
+        }
+
+        // update the shard commitment, then its digest and the length in the top level
+        self.shards[shard][offset] = value;
+        self.shard_commitments[shard] = self.asvc.update_commitment(self.shard_commitments[shard], offset, value);
+        self.set_top(shard + 1, commitment_to_field::<E>(&self.shard_commitments[shard]));
+        self.len += 1;
+        self.set_top(0, E::ScalarField::from(self.len as u64));
+        Ok(())
+    }
+
+    pub fn extend(&mut self, values: &[E::ScalarField]) -> Result<(), &'static str> {
+        for &value in values {
+            self.append(value)?;
+        }
+        Ok(())
+    }
+
+    // prove the values at the given indices, which may span several shards
+    pub fn prove_positions(&self, indices: &[usize]) -> ShardedProof<E> {
+        let degree = self.asvc.degree;
+        let mut by_shard: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+        for &index in indices {
+            assert!(index < self.len, "index out of bounds");
+            let offsets = by_shard.entry(index / degree).or_default();
+            if !offsets.contains(&(index % degree)) {
+                offsets.push(index % degree);
+            }
+        }
+
+        let mut shard_commitments = vec![];
+        let mut shard_proofs = vec![];
+        let mut top_indices = vec![0];
+        for (&shard, offsets) in &by_shard {
+            shard_commitments.push(self.shard_commitments[shard]);
+            shard_proofs.push(self.asvc.prove_position(offsets, &self.shards[shard])
+                .expect("offsets are distinct positions of the shard"));
+            top_indices.push(shard + 1);
+        }
+
+        ShardedProof {
+            len: self.len,
+            shard_commitments,
+            shard_proofs,
+            top_proof: self.asvc.prove_position(&top_indices, &self.top)
+                .expect("the length and the shard digests are distinct top-level positions"),
+        }
+    }
+
+    // verify values at the given indices against the top-level commitment
+    pub fn verify_positions(
+        asvc: &ASVC<E>,
+        commitment: E::G1,
+        indices: &[usize],
+        values: &[E::ScalarField],
+        proof: &ShardedProof<E>,
+    ) -> bool {
+        if indices.len() != values.len() {
+            return false;
+        }
+        let degree = asvc.degree;
+        let mut by_shard: BTreeMap<usize, BTreeMap<usize, E::ScalarField>> = BTreeMap::new();
+        for (&index, &value) in indices.iter().zip(values) {
+            if index >= proof.len {
+                return false;
+            }
+            let shard = by_shard.entry(index / degree).or_default();
+            if *shard.entry(index % degree).or_insert(value) != value {
+                return false;
+            }
+        }
+        if by_shard.len() != proof.shard_commitments.len() || by_shard.len() != proof.shard_proofs.len() {
+            return false;
+        }
+
+        // check each shard opening against its claimed commitment
+        let mut top_indices = vec![0];
+        let mut top_values = vec![E::ScalarField::from(proof.len as u64)];
+        for (((&shard, entries), shard_commitment), &shard_proof) in by_shard.iter()
+            .zip(&proof.shard_commitments)
+            .zip(&proof.shard_proofs)
+        {
+            let offsets: Vec<usize> = entries.keys().cloned().collect();
+            let subvector: Vec<E::ScalarField> = entries.values().cloned().collect();
+            if !asvc.verify_positon(*shard_commitment, &offsets, &subvector, shard_proof) {
+                return false;
+            }
+            top_indices.push(shard + 1);
+            top_values.push(commitment_to_field::<E>(shard_commitment));
+        }
+
+        // check the length and shard digests against the top-level commitment
+        asvc.verify_positon(commitment, &top_indices, &top_values, proof.top_proof)
+    }
+
+    fn set_top(&mut self, index: usize, value: E::ScalarField) {
+        let delta = value - self.top[index];
+        self.root = self.asvc.update_commitment(self.root, index, delta);
+        self.top[index] = value;
+    }
+}
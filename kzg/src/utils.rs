@@ -1,5 +1,8 @@
 use ark_ff::{Field, PrimeField};
+use ark_ec::pairing::Pairing;
+use ark_serialize::CanonicalSerialize;
 use ark_std::log2;
+use sha2::{Digest, Sha256};
 
 // helper functio for polynomial addition
 pub fn add<E:Field>(p1: &[E], p2: &[E]) -> Vec<E> {
@@ -64,7 +67,7 @@ pub fn evaluate<E:Field>(p: &[E], x: E) -> E {
     let mut result = E::ZERO;
 
     for (i, &coeff) in p.iter().enumerate() {
-        result += coeff * x.pow(&[i as u64]);
+        result += coeff * x.pow([i as u64]);
     }
 
     result
@@ -122,6 +125,28 @@ pub fn scalar_mul<E:Field>(poly: &[E], scalar:E) -> Vec<E> {
         result.push(*coeff * scalar);
     }    
     result
-}   
+}
+
+// helper function to hash arbitrary bytes to a scalar
+pub fn hash_to_field<E: PrimeField>(bytes: &[u8]) -> E {
+    E::from_le_bytes_mod_order(&Sha256::digest(bytes))
+}
 
+// helper function to hash bytes behind a one-byte domain separation tag
+pub fn tagged_hash_to_field<E: PrimeField>(tag: u8, bytes: &[u8]) -> E {
+    let mut hasher = Sha256::new();
+    hasher.update([tag]);
+    hasher.update(bytes);
+    E::from_le_bytes_mod_order(&hasher.finalize())
+}
+
+// tag of commitment digests, which must differ from the tags of any other digest
+// committed next to them, whatever the encoding of the curve
+pub const COMMITMENT_TAG: u8 = 0;
 
+// helper function to map a commitment to a scalar by hashing its tagged compressed encoding
+pub fn commitment_to_field<E: Pairing>(commitment: &E::G1) -> E::ScalarField {
+    let mut bytes = Vec::new();
+    commitment.serialize_compressed(&mut bytes).unwrap();
+    tagged_hash_to_field(COMMITMENT_TAG, &bytes)
+}
@@ -0,0 +1,419 @@
+//! Verkle trie built from ASVC commitments.
+//!
+//! Every internal node is a vector of `width` scalars committed with ASVC, where `width`
+//! is the ASVC degree (256 for the usual Verkle layout), and each level of the trie
+//! consumes log2(width) bits of the key. A slot holds zero when empty, the digest of the
+//! child commitment for internal children, or a digest of key and value for leaves.
+//! Multiproofs open every (node, slot) pair on the looked up paths with a single
+//! aggregated KZG opening, following https://dankradfeist.de/ethereum/2021/06/18/pcs-multiproofs.html
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::ops::Mul;
+use ark_ff::{Field, PrimeField, Zero};
+use ark_ec::pairing::Pairing;
+use ark_poly::{EvaluationDomain, Radix2EvaluationDomain};
+use ark_serialize::{CanonicalSerialize, CanonicalDeserialize};
+use crate::asvc::ASVC;
+use crate::map::Key;
+use crate::utils::{commitment_to_field, hash_to_field, tagged_hash_to_field, div, evaluate};
+
+/// how the path of a looked up key ends
+#[derive(Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct PathInfo {
+    /// number of internal nodes on the path, including the root
+    pub depth: u16,
+    /// leaf of another key found where a missing key would be stored
+    pub other_leaf: Option<(Key, Vec<u8>)>,
+}
+
+#[derive(Clone, CanonicalSerialize, CanonicalDeserialize)]
+pub struct VerkleProof<E: Pairing> {
+    /// commitments of the internal nodes below the root on the looked up paths,
+    /// ordered by their path from the root
+    pub commitments: Vec<E::G1>,
+    /// one entry per looked up key
+    pub paths: Vec<PathInfo>,
+    /// commitment to the aggregated quotient polynomial g(X)
+    pub d: E::G1,
+    /// KZG proof for the opening of h(X) - g(X) at the challenge point
+    pub pi: E::G1,
+}
+
+enum Node<E: Pairing> {
+    Empty,
+    Leaf { key: Key, value: Vec<u8> },
+    Internal(Box<InternalNode<E>>),
+}
+
+struct InternalNode<E: Pairing> {
+    children: Vec<Node<E>>,
+    values: Vec<E::ScalarField>,
+    commitment: E::G1,
+}
+
+// commitment, evaluations and opened slot of one node in a multiproof
+type NodeOpening<'b, E> = (<E as Pairing>::G1, &'b [<E as Pairing>::ScalarField], usize);
+
+pub struct VerkleTree<'a, E: Pairing> {
+    asvc: &'a ASVC<E>,
+    root: InternalNode<E>,
+}
+
+impl<'a, E: Pairing> VerkleTree<'a, E> {
+    pub fn new(asvc: &'a ASVC<E>) -> Self {
+        assert!(asvc.degree.is_power_of_two() && asvc.degree <= 256, "width must be a power of two up to 256");
+        Self {
+            asvc,
+            root: InternalNode::new(asvc),
+        }
+    }
+
+    pub fn commitment(&self) -> E::G1 {
+        self.root.commitment
+    }
+
+    pub fn get(&self, key: &Key) -> Option<&[u8]> {
+        let mut node = &self.root;
+        let mut depth = 0;
+        loop {
+            match &node.children[key_digit(key, depth, self.asvc.degree)] {
+                Node::Internal(child) => node = child,
+                Node::Leaf { key: leaf_key, value } if leaf_key == key => return Some(value),
+                _ => return None,
+            }
+            depth += 1;
+        }
+    }
+
+    // insert or update a key, returning the previous value
+    pub fn insert(&mut self, key: &Key, value: &[u8]) -> Option<Vec<u8>> {
+        insert_at(self.asvc, &mut self.root, 0, key, value.to_vec())
+    }
+
+    // delete a key, returning its value
+    pub fn remove(&mut self, key: &Key) -> Option<Vec<u8>> {
+        remove_at(self.asvc, &mut self.root, 0, key)
+    }
+
+    // look up several keys and prove the result with one aggregated opening
+    pub fn prove(&self, keys: &[Key]) -> (Vec<Option<Vec<u8>>>, VerkleProof<E>) {
+        let width = self.asvc.degree;
+        let mut values = Vec::with_capacity(keys.len());
+        let mut paths = Vec::with_capacity(keys.len());
+        let mut nodes: BTreeMap<Vec<usize>, &InternalNode<E>> = BTreeMap::new();
+        let mut openings: BTreeSet<(Vec<usize>, usize)> = BTreeSet::new();
+
+        for key in keys {
+            let mut node = &self.root;
+            let mut prefix = vec![];
+            loop {
+                nodes.insert(prefix.clone(), node);
+                let digit = key_digit(key, prefix.len(), width);
+                openings.insert((prefix.clone(), digit));
+                let (value, other_leaf) = match &node.children[digit] {
+                    Node::Internal(child) => {
+                        node = child;
+                        prefix.push(digit);
+                        continue;
+                    }
+                    Node::Leaf { key: leaf_key, value } if leaf_key == key => (Some(value.clone()), None),
+                    Node::Leaf { key: leaf_key, value } => (None, Some((*leaf_key, value.clone()))),
+                    Node::Empty => (None, None),
+                };
+                values.push(value);
+                paths.push(PathInfo { depth: prefix.len() as u16 + 1, other_leaf });
+                break;
+            }
+        }
+
+        let commitments = nodes.iter()
+            .filter(|(prefix, _)| !prefix.is_empty())
+            .map(|(_, node)| node.commitment)
+            .collect();
+        let openings: Vec<NodeOpening<E>> = openings.iter()
+            .map(|(prefix, digit)| {
+                let node = nodes[prefix];
+                (node.commitment, &node.values[..], *digit)
+            })
+            .collect();
+        let (d, pi) = multi_open(self.asvc, &openings);
+
+        (values, VerkleProof { commitments, paths, d, pi })
+    }
+
+    // verify the values (None for missing keys) of several keys against a root commitment
+    pub fn verify(
+        asvc: &ASVC<E>,
+        commitment: E::G1,
+        keys: &[Key],
+        values: &[Option<&[u8]>],
+        proof: &VerkleProof<E>,
+    ) -> bool {
+        let width = asvc.degree;
+        if keys.len() != values.len() || keys.len() != proof.paths.len() {
+            return false;
+        }
+
+        // assign the listed commitments to the internal nodes on the paths
+        let mut prefixes = BTreeSet::new();
+        for (key, path) in keys.iter().zip(&proof.paths) {
+            let depth = path.depth as usize;
+            if depth == 0 || depth * bits_per_level(width) > 256 {
+                return false;
+            }
+            for level in 1..depth {
+                prefixes.insert(key_prefix(key, level, width));
+            }
+        }
+        if prefixes.len() != proof.commitments.len() {
+            return false;
+        }
+        let mut commitments: BTreeMap<Vec<usize>, E::G1> = prefixes.into_iter().zip(proof.commitments.iter().cloned()).collect();
+        commitments.insert(vec![], commitment);
+
+        // derive the expected value of every opened slot, rejecting conflicting claims
+        let mut openings: BTreeMap<(Vec<usize>, usize), E::ScalarField> = BTreeMap::new();
+        for ((key, value), path) in keys.iter().zip(values).zip(&proof.paths) {
+            let depth = path.depth as usize;
+            for level in 0..depth {
+                let digit = key_digit(key, level, width);
+                let expected = if level + 1 < depth {
+                    commitment_to_field::<E>(&commitments[&key_prefix(key, level + 1, width)])
+                } else {
+                    match (value, &path.other_leaf) {
+                        (Some(value), None) => leaf_digest(key, value),
+                        (None, None) => E::ScalarField::ZERO,
+                        (None, Some((other_key, other_value))) => {
+                            if other_key == key || key_prefix(other_key, depth, width) != key_prefix(key, depth, width) {
+                                return false;
+                            }
+                            leaf_digest(other_key, other_value)
+                        }
+                        (Some(_), Some(_)) => return false,
+                    }
+                };
+                let prefix = key_prefix(key, level, width);
+                if *openings.entry((prefix, digit)).or_insert(expected) != expected {
+                    return false;
+                }
+            }
+        }
+
+        let openings: Vec<(E::G1, usize, E::ScalarField)> = openings.into_iter()
+            .map(|((prefix, digit), y)| (commitments[&prefix], digit, y))
+            .collect();
+        verify_multi_open(asvc, &openings, proof.d, proof.pi)
+    }
+}
+
+impl<E: Pairing> InternalNode<E> {
+    fn new(asvc: &ASVC<E>) -> Self {
+        let values = vec![E::ScalarField::ZERO; asvc.degree];
+        Self {
+            children: (0..asvc.degree).map(|_| Node::Empty).collect(),
+            commitment: asvc.vector_commit(&values),
+            values,
+        }
+    }
+
+    // refresh the slot of a child after it changed, updating the commitment
+    fn refresh(&mut self, asvc: &ASVC<E>, digit: usize) {
+        let value = match &self.children[digit] {
+            Node::Empty => E::ScalarField::ZERO,
+            Node::Leaf { key, value } => leaf_digest(key, value),
+            Node::Internal(child) => commitment_to_field::<E>(&child.commitment),
+        };
+        let delta = value - self.values[digit];
+        if !delta.is_zero() {
+            self.commitment = asvc.update_commitment(self.commitment, digit, delta);
+            self.values[digit] = value;
+        }
+    }
+}
+
+fn insert_at<E: Pairing>(
+    asvc: &ASVC<E>,
+    node: &mut InternalNode<E>,
+    depth: usize,
+    key: &Key,
+    value: Vec<u8>,
+) -> Option<Vec<u8>> {
+    let digit = key_digit(key, depth, asvc.degree);
+    let (child, old) = match std::mem::replace(&mut node.children[digit], Node::Empty) {
+        Node::Empty => (Node::Leaf { key: *key, value }, None),
+        Node::Leaf { key: leaf_key, value: old } if leaf_key == *key => (Node::Leaf { key: leaf_key, value }, Some(old)),
+        Node::Leaf { key: leaf_key, value: leaf_value } => {
+            // push both leaves one level down until their paths diverge
+            let mut child = InternalNode::new(asvc);
+            insert_at(asvc, &mut child, depth + 1, &leaf_key, leaf_value);
+            insert_at(asvc, &mut child, depth + 1, key, value);
+            (Node::Internal(Box::new(child)), None)
+        }
+        Node::Internal(mut child) => {
+            let old = insert_at(asvc, &mut child, depth + 1, key, value);
+            (Node::Internal(child), old)
+        }
+    };
+    node.children[digit] = child;
+    node.refresh(asvc, digit);
+    old
+}
+
+fn remove_at<E: Pairing>(
+    asvc: &ASVC<E>,
+    node: &mut InternalNode<E>,
+    depth: usize,
+    key: &Key,
+) -> Option<Vec<u8>> {
+    let digit = key_digit(key, depth, asvc.degree);
+    let (child, old) = match std::mem::replace(&mut node.children[digit], Node::Empty) {
+        Node::Leaf { key: leaf_key, value } if leaf_key == *key => (Node::Empty, Some(value)),
+        Node::Internal(mut child) => {
+            let old = remove_at(asvc, &mut child, depth + 1, key);
+            // collapse a node left with a single leaf so the trie shape stays canonical
+            let mut remaining = child.children.iter().filter(|c| !matches!(c, Node::Empty));
+            match (remaining.next(), remaining.next()) {
+                (None, _) => (Node::Empty, old),
+                (Some(Node::Leaf { .. }), None) => {
+                    let leaf = child.children.into_iter().find(|c| !matches!(c, Node::Empty)).unwrap();
+                    (leaf, old)
+                }
+                _ => (Node::Internal(child), old),
+            }
+        }
+        other => (other, None),
+    };
+    node.children[digit] = child;
+    node.refresh(asvc, digit);
+    old
+}
+
+fn bits_per_level(width: usize) -> usize {
+    width.trailing_zeros() as usize
+}
+
+// child index of a key at the given depth, read from the key bits most significant first
+fn key_digit(key: &Key, depth: usize, width: usize) -> usize {
+    let bits = bits_per_level(width);
+    let mut digit = 0;
+    for bit in depth * bits..(depth + 1) * bits {
+        digit = (digit << 1) | ((key[bit / 8] >> (7 - bit % 8)) & 1) as usize;
+    }
+    digit
+}
+
+fn key_prefix(key: &Key, depth: usize, width: usize) -> Vec<usize> {
+    (0..depth).map(|level| key_digit(key, level, width)).collect()
+}
+
+// tag of leaf digests, distinct from COMMITMENT_TAG so that a leaf never shares the
+// digest of an inner commitment
+const LEAF_TAG: u8 = 1;
+
+pub(crate) fn leaf_digest<F: PrimeField>(key: &Key, value: &[u8]) -> F {
+    let mut bytes = key.to_vec();
+    bytes.extend_from_slice(value);
+    tagged_hash_to_field(LEAF_TAG, &bytes)
+}
+
+fn commit<E: Pairing>(asvc: &ASVC<E>, coefficients: &[E::ScalarField]) -> E::G1 {
+    let crs = &asvc.proving_key.crs.g1;
+    coefficients.iter().zip(crs).map(|(coeff, g)| g.mul(coeff)).sum()
+}
+
+// challenge r binding all opened commitments, slots and values
+fn opening_challenge<E: Pairing>(openings: impl Iterator<Item = (E::G1, usize, E::ScalarField)>) -> E::ScalarField {
+    let mut bytes = Vec::new();
+    for (commitment, digit, y) in openings {
+        commitment.serialize_compressed(&mut bytes).unwrap();
+        (digit as u64).serialize_compressed(&mut bytes).unwrap();
+        y.serialize_compressed(&mut bytes).unwrap();
+    }
+    hash_to_field(&bytes)
+}
+
+// challenge t at which h(X) - g(X) is opened
+fn evaluation_challenge<E: Pairing>(r: E::ScalarField, d: E::G1) -> E::ScalarField {
+    let mut bytes = Vec::new();
+    r.serialize_compressed(&mut bytes).unwrap();
+    d.serialize_compressed(&mut bytes).unwrap();
+    hash_to_field(&bytes)
+}
+
+// open polynomials f_i given in evaluation form at w^{z_i} with a single proof (D, pi)
+fn multi_open<E: Pairing>(asvc: &ASVC<E>, openings: &[NodeOpening<E>]) -> (E::G1, E::G1) {
+    let domain = Radix2EvaluationDomain::<E::ScalarField>::new(asvc.degree).unwrap();
+    let r = opening_challenge::<E>(openings.iter().map(|(c, f, z)| (*c, *z, f[*z])));
+
+    // g(X) = sum r^i (f_i(X) - y_i) / (X - w^{z_i}), grouping openings at the same point
+    let mut grouped: BTreeMap<usize, Vec<E::ScalarField>> = BTreeMap::new();
+    let mut power = E::ScalarField::ONE;
+    for (_, f, z) in openings {
+        let group = grouped.entry(*z).or_insert_with(|| vec![E::ScalarField::ZERO; asvc.degree]);
+        for (acc, value) in group.iter_mut().zip(f.iter()) {
+            *acc += power * (*value - f[*z]);
+        }
+        power *= r;
+    }
+    let mut g = vec![E::ScalarField::ZERO; asvc.degree];
+    for (z, evaluations) in grouped {
+        let numerator = domain.ifft(&evaluations);
+        let quotient = div(&numerator, &[-domain.element(z), E::ScalarField::ONE]).unwrap();
+        for (acc, coeff) in g.iter_mut().zip(quotient) {
+            *acc += coeff;
+        }
+    }
+    let d = commit(asvc, &g);
+
+    // h(X) = sum r^i f_i(X) / (t - w^{z_i})
+    let t = evaluation_challenge::<E>(r, d);
+    let mut h = vec![E::ScalarField::ZERO; asvc.degree];
+    let mut power = E::ScalarField::ONE;
+    for (_, f, z) in openings {
+        let scale = power * (t - domain.element(*z)).inverse().unwrap();
+        for (acc, value) in h.iter_mut().zip(f.iter()) {
+            *acc += scale * value;
+        }
+        power *= r;
+    }
+    let mut q = domain.ifft(&h);
+    for (acc, coeff) in q.iter_mut().zip(g) {
+        *acc -= coeff;
+    }
+
+    // KZG proof that h(X) - g(X) evaluates to q(t) at t
+    let value = evaluate(&q, t);
+    q[0] -= value;
+    let quotient = div(&q, &[-t, E::ScalarField::ONE]).unwrap();
+    (d, commit(asvc, &quotient))
+}
+
+fn verify_multi_open<E: Pairing>(
+    asvc: &ASVC<E>,
+    openings: &[(E::G1, usize, E::ScalarField)],
+    d: E::G1,
+    pi: E::G1,
+) -> bool {
+    let domain = Radix2EvaluationDomain::<E::ScalarField>::new(asvc.degree).unwrap();
+    let r = opening_challenge::<E>(openings.iter().cloned());
+    let t = evaluation_challenge::<E>(r, d);
+
+    // E = sum r^i / (t - w^{z_i}) C_i and the claimed value sum r^i y_i / (t - w^{z_i})
+    let mut e = E::G1::zero();
+    let mut value = E::ScalarField::ZERO;
+    let mut power = E::ScalarField::ONE;
+    for (commitment, z, y) in openings {
+        let scale = match (t - domain.element(*z)).inverse() {
+            Some(inverse) => power * inverse,
+            None => return false,
+        };
+        e += commitment.mul(scale);
+        value += scale * y;
+        power *= r;
+    }
+
+    let crs = &asvc.verification_key.crs;
+    let lhs = E::pairing(pi, crs.g2[1] - crs.g2[0].mul(t));
+    let rhs = E::pairing(e - d - crs.g1[0].mul(value), crs.g2[0]);
+    lhs == rhs
+}
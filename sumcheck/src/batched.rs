@@ -0,0 +1,124 @@
+//! Batched sumcheck for several claims `sum g_i = c_i`.
+//!
+//! The verifier picks random coefficients a_i and a single sumcheck runs on
+//! `sum a_i g_i`. A polynomial with fewer variables than the largest one is padded
+//! with front-loaded variables it does not depend on, so that its variables are bound
+//! in the last rounds; its sum over the padded hypercube is `2^k c_i` for k padding
+//! variables. The prover ends with the evaluation of every g_i at its part of the
+//! sumcheck point, which the verifier checks against the final claim and returns as
+//! one subclaim per polynomial.
+
+use ark_poly::DenseMultilinearExtension;
+use ark_ff::Field;
+use ark_serialize::{CanonicalSerialize, CanonicalDeserialize};
+use crate::IPForSumCheck;
+use crate::error::SumcheckError;
+use crate::proof::SumcheckProof;
+use crate::transcript::Transcript;
+use crate::verifier::SubClaim;
+use crate::virtual_poly::{VirtualPolynomial, VPAuxInfo};
+
+#[derive(Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct BatchedSumcheckProof<F: Field> {
+    /// sum claimed for each polynomial
+    pub claimed_values: Vec<F>,
+    /// sumcheck for the random combination of the padded polynomials
+    pub sumcheck: SumcheckProof<F>,
+    /// evaluation of each polynomial at its part of the sumcheck point
+    pub evaluations: Vec<F>,
+}
+
+impl<F: Field> IPForSumCheck<F> {
+    pub fn batch_prove(
+        polynomials: &[VirtualPolynomial<F>],
+        transcript: &mut Transcript
+    ) -> BatchedSumcheckProof<F> {
+        assert!(!polynomials.is_empty(), "batch must hold at least one polynomial");
+        let claimed_values: Vec<F> = polynomials.iter()
+            .map(|polynomial| Self::vp_run_init_prover_round(&mut Self::vp_prover_init(polynomial)))
+            .collect();
+        let coefficients = batch_coefficients(&claimed_values, transcript);
+
+        // pad every polynomial to the largest variable count and combine the terms
+        let num_vars = polynomials.iter().map(|polynomial| polynomial.num_vars).max().unwrap();
+        let mut combined = VirtualPolynomial {
+            num_vars,
+            mles: vec![],
+            products: vec![],
+        };
+        for (polynomial, &coefficient) in polynomials.iter().zip(&coefficients) {
+            let padding = num_vars - polynomial.num_vars;
+            let offset = combined.mles.len();
+            for mle in &polynomial.mles {
+                let evaluations = (0..1 << num_vars).map(|index| mle.evaluations[index >> padding]).collect();
+                combined.add_mle(DenseMultilinearExtension::from_evaluations_vec(num_vars, evaluations));
+            }
+            for (term_coefficient, indices) in &polynomial.products {
+                combined.add_product(
+                    coefficient * term_coefficient,
+                    indices.iter().map(|index| offset + index).collect()
+                );
+            }
+        }
+
+        let (sumcheck, point) = Self::vp_prove_with_randomness(&combined, transcript);
+        let evaluations = polynomials.iter()
+            .map(|polynomial| polynomial.evaluate(&point[num_vars - polynomial.num_vars..]))
+            .collect();
+        transcript.append_serializable(b"batch_evaluations", &evaluations);
+
+        BatchedSumcheckProof { claimed_values, sumcheck, evaluations }
+    }
+
+    // check the claimed sums and return one evaluation subclaim per polynomial
+    pub fn batch_verify(
+        proof: &BatchedSumcheckProof<F>,
+        claimed_values: &[F],
+        aux_infos: &[VPAuxInfo],
+        transcript: &mut Transcript
+    ) -> Result<Vec<SubClaim<F>>, SumcheckError> {
+        if aux_infos.is_empty()
+            || proof.claimed_values.len() != aux_infos.len()
+            || proof.evaluations.len() != aux_infos.len()
+        {
+            return Err(SumcheckError::MalformedProof("wrong number of polynomials"));
+        }
+        if proof.claimed_values != claimed_values {
+            return Err(SumcheckError::ClaimedValueMismatch);
+        }
+        let coefficients = batch_coefficients(claimed_values, transcript);
+
+        let num_vars = aux_infos.iter().map(|aux_info| aux_info.num_vars).max().unwrap();
+        let max_degree = aux_infos.iter().map(|aux_info| aux_info.max_degree).max().unwrap();
+        let combined_value: F = aux_infos.iter()
+            .zip(claimed_values)
+            .zip(&coefficients)
+            .map(|((aux_info, &value), &coefficient)| {
+                coefficient * value * F::from(1u64 << (num_vars - aux_info.num_vars))
+            })
+            .sum();
+        let subclaim = Self::verify(&proof.sumcheck, combined_value, &vec![max_degree; num_vars], transcript)?;
+        let combined_evaluation: F = coefficients.iter()
+            .zip(&proof.evaluations)
+            .map(|(&coefficient, &evaluation)| coefficient * evaluation)
+            .sum();
+        if combined_evaluation != subclaim.expected_evaluation {
+            return Err(SumcheckError::FinalEvaluationCheckFailed);
+        }
+        transcript.append_serializable(b"batch_evaluations", &proof.evaluations);
+
+        Ok(aux_infos.iter()
+            .zip(&proof.evaluations)
+            .map(|(aux_info, &evaluation)| SubClaim {
+                point: subclaim.point[num_vars - aux_info.num_vars..].to_vec(),
+                expected_evaluation: evaluation,
+            })
+            .collect())
+    }
+}
+
+// absorb the claims, then derive one coefficient per claim
+fn batch_coefficients<F: Field>(claimed_values: &[F], transcript: &mut Transcript) -> Vec<F> {
+    transcript.append_serializable(b"batch_claimed_values", &claimed_values.to_vec());
+    claimed_values.iter().map(|_| transcript.challenge_scalar(b"batch_coefficient")).collect()
+}
@@ -0,0 +1,58 @@
+use std::fmt;
+
+/// reasons for the sumcheck verifier to reject a proof
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SumcheckError {
+    /// proof is for a different sum than the one being checked
+    ClaimedValueMismatch,
+    /// g1(0) + g1(1) differs from the claimed sum
+    FirstEvaluationCheckFailed,
+    /// gj(0) + gj(1) differs from gj-1(rj-1)
+    RoundEvaluationCheckFailed { round: usize },
+    /// round polynomial has a larger degree than its variable in the polynomial
+    DegreeTooHigh { round: usize, degree: usize, max_degree: usize },
+    /// number of rounds differs from the number of variables
+    WrongNumberOfRounds { expected: usize, actual: usize },
+    /// gv(rv) differs from the polynomial evaluated at the sampled point
+    FinalEvaluationCheckFailed,
+    /// a GKR layer does not match the claims about the next layer
+    LayerEvaluationCheckFailed { layer: usize },
+    /// the inputs do not match the claim left by the last GKR layer
+    InputEvaluationCheckFailed,
+    /// the products of the memory-checking multisets differ
+    MultisetMismatch,
+    /// a memory access reads a timestamp that is not earlier than its own step
+    ReadTimestampNotEarlier { access: usize },
+    /// proof does not have the shape expected by the verifier
+    MalformedProof(&'static str),
+}
+
+impl fmt::Display for SumcheckError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SumcheckError::ClaimedValueMismatch => write!(f, "proof is for a different claimed value"),
+            SumcheckError::FirstEvaluationCheckFailed => write!(f, "first evaluation check failed"),
+            SumcheckError::RoundEvaluationCheckFailed { round } => {
+                write!(f, "round evaluation check failed at round {}", round)
+            }
+            SumcheckError::DegreeTooHigh { round, degree, max_degree } => write!(
+                f, "round {} polynomial has degree {}, expected at most {}", round, degree, max_degree
+            ),
+            SumcheckError::WrongNumberOfRounds { expected, actual } => {
+                write!(f, "expected {} rounds, got {}", expected, actual)
+            }
+            SumcheckError::FinalEvaluationCheckFailed => write!(f, "final evaluation check failed"),
+            SumcheckError::LayerEvaluationCheckFailed { layer } => {
+                write!(f, "evaluation check failed at layer {}", layer)
+            }
+            SumcheckError::InputEvaluationCheckFailed => write!(f, "input evaluation check failed"),
+            SumcheckError::MultisetMismatch => write!(f, "init and write sets differ from read and final sets"),
+            SumcheckError::ReadTimestampNotEarlier { access } => {
+                write!(f, "access {} reads a timestamp not earlier than its step", access)
+            }
+            SumcheckError::MalformedProof(reason) => write!(f, "malformed proof: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for SumcheckError {}
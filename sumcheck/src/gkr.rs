@@ -0,0 +1,312 @@
+//! GKR protocol for layered arithmetic circuits (Thaler, section 4.6).
+//!
+//! Layer 0 holds the outputs and the gates of layer i read their two inputs from
+//! layer i + 1, the last layer reading from the circuit inputs. Each layer is reduced
+//! to the next one with a sumcheck over
+//! `add_i(r, b, c) (W(b) + W(c)) + mul_i(r, b, c) W(b) W(c)`, and the two resulting
+//! claims about `W(b)` and `W(c)` are reduced to one by restricting `W` to the line
+//! through b and c. The prover runs the sumcheck in two phases, over b and then over
+//! c (Xie et al., Libra), so that its tables have the size of a layer rather than its
+//! square and it runs in time linear in the size of the layers. The verifier evaluates
+//! the wiring predicates itself and only evaluates the multilinear extension of the
+//! inputs at the end.
+
+use ark_poly::{DenseMultilinearExtension, MultilinearExtension, Polynomial};
+use ark_ff::Field;
+use ark_serialize::{CanonicalSerialize, CanonicalDeserialize};
+use crate::IPForSumCheck;
+use crate::error::SumcheckError;
+use crate::proof::SumcheckProof;
+use crate::prover::ProverMsg;
+use crate::transcript::Transcript;
+use crate::utils::{eq_eval_at_index, eq_table, interpolate_uni_poly};
+use crate::virtual_poly::VirtualPolynomial;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Gate {
+    /// sum of the two given gates of the next layer
+    Add(usize, usize),
+    /// product of the two given gates of the next layer
+    Mul(usize, usize),
+}
+
+#[derive(Clone, Debug)]
+pub struct Layer {
+    pub gates: Vec<Gate>,
+}
+
+#[derive(Clone, Debug)]
+pub struct LayeredCircuit {
+    /// layers from the outputs down to the layer reading the inputs
+    pub layers: Vec<Layer>,
+    pub num_inputs: usize,
+}
+
+/// messages of the prover for the reduction of one layer to the next
+#[derive(Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct GKRLayerProof<F: Field> {
+    /// sumcheck over the wiring predicates of the layer
+    pub sumcheck: SumcheckProof<F>,
+    /// evaluations at 0, 1, ..., k of the next layer restricted to the line through b and c
+    pub line_evaluations: Vec<F>,
+}
+
+#[derive(Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct GKRProof<F: Field> {
+    /// one reduction per layer, starting at the outputs
+    pub layers: Vec<GKRLayerProof<F>>,
+}
+
+impl LayeredCircuit {
+    pub fn new(layers: Vec<Layer>, num_inputs: usize) -> Self {
+        assert!(!layers.is_empty(), "circuit must have at least one layer");
+        for (i, layer) in layers.iter().enumerate() {
+            assert!(!layer.gates.is_empty(), "layer must have at least one gate");
+            let width = layers.get(i + 1).map_or(num_inputs, |next| next.gates.len());
+            for gate in &layer.gates {
+                let (left, right) = gate.inputs();
+                assert!(left < width && right < width, "gate reads a missing wire");
+            }
+        }
+        LayeredCircuit { layers, num_inputs }
+    }
+
+    // values of every layer, followed by the inputs
+    pub fn evaluate<F: Field>(&self, inputs: &[F]) -> Vec<Vec<F>> {
+        assert_eq!(inputs.len(), self.num_inputs, "wrong number of inputs");
+        let mut values = vec![inputs.to_vec()];
+        for layer in self.layers.iter().rev() {
+            let next = values.last().unwrap();
+            let current = layer.gates.iter()
+                .map(|gate| match *gate {
+                    Gate::Add(left, right) => next[left] + next[right],
+                    Gate::Mul(left, right) => next[left] * next[right],
+                })
+                .collect();
+            values.push(current);
+        }
+        values.reverse();
+        values
+    }
+
+    // number of variables of the multilinear extension of layer i, the inputs being
+    // the layer after the last one
+    pub fn num_vars(&self, i: usize) -> usize {
+        let width = self.layers.get(i).map_or(self.num_inputs, |layer| layer.gates.len());
+        log_size(width)
+    }
+}
+
+impl Gate {
+    pub fn inputs(&self) -> (usize, usize) {
+        match *self {
+            Gate::Add(left, right) | Gate::Mul(left, right) => (left, right),
+        }
+    }
+}
+
+impl<F: Field> IPForSumCheck<F> {
+    // evaluate the circuit and prove that the returned outputs are correct
+    pub fn gkr_prove(
+        circuit: &LayeredCircuit,
+        inputs: &[F],
+        transcript: &mut Transcript
+    ) -> (Vec<F>, GKRProof<F>) {
+        let values = circuit.evaluate(inputs);
+        let outputs = values[0].clone();
+
+        let num_vars = circuit.num_vars(0);
+        transcript.append_serializable(b"outputs", &outputs);
+        let mut point: Vec<F> = (0..num_vars).map(|_| transcript.challenge_scalar(b"output_point")).collect();
+
+        let mut layers = Vec::with_capacity(circuit.layers.len());
+        for (i, layer) in circuit.layers.iter().enumerate() {
+            let next_vars = circuit.num_vars(i + 1);
+            let next = to_mle(&values[i + 1], next_vars);
+
+            // first the rounds over b, then those over c once b is fixed, so that no
+            // table has more than 2^next_vars entries
+            let eq = eq_table(&point);
+            let first = wiring_over_b(layer, &eq, &next);
+            let mut prover_state = Self::vp_prover_init(&first);
+            let claimed_value = Self::vp_run_init_prover_round(&mut prover_state);
+            let mut second_state = None;
+            let prove_round = |verifier_msg: &Option<_>| {
+                let prover_msg = match (&mut second_state, verifier_msg) {
+                    (Some(state), _) => Self::vp_prove_round(state, verifier_msg),
+                    (None, Some(msg)) if prover_state.round == next_vars => {
+                        let mut b = prover_state.randomness.clone();
+                        b.push(msg.randomn_value);
+                        let second = wiring_over_c(layer, &eq, &next, &b);
+                        let state = second_state.insert(Self::vp_prover_init(&second));
+                        Self::vp_prove_round(state, &None)
+                    },
+                    (None, _) => Self::vp_prove_round(&mut prover_state, verifier_msg),
+                };
+                ProverMsg { uvpolynomial: prover_msg.to_uvpolynomial() }
+            };
+            let (sumcheck, randomness) = Self::prove_rounds(&vec![2; 2 * next_vars], claimed_value, transcript, prove_round);
+
+            // restrict the next layer to the line through b and c
+            let (b, c) = randomness.split_at(next_vars);
+            let line_evaluations: Vec<F> = (0..=next_vars)
+                .map(|t| next.evaluate(&line_point(b, c, F::from(t as u64))).unwrap())
+                .collect();
+            transcript.append_serializable(b"line_evaluations", &line_evaluations);
+            let t = transcript.challenge_scalar(b"line_challenge");
+            point = line_point(b, c, t);
+
+            layers.push(GKRLayerProof { sumcheck, line_evaluations });
+        }
+
+        (outputs, GKRProof { layers })
+    }
+
+    // check the claimed outputs, evaluating only the inputs' multilinear extension
+    pub fn gkr_verify(
+        circuit: &LayeredCircuit,
+        inputs: &[F],
+        outputs: &[F],
+        proof: &GKRProof<F>,
+        transcript: &mut Transcript
+    ) -> Result<(), SumcheckError> {
+        if outputs.len() != circuit.layers[0].gates.len() || inputs.len() != circuit.num_inputs {
+            return Err(SumcheckError::MalformedProof("wrong number of inputs or outputs"));
+        }
+        if proof.layers.len() != circuit.layers.len() {
+            return Err(SumcheckError::MalformedProof("wrong number of layer proofs"));
+        }
+
+        let num_vars = circuit.num_vars(0);
+        transcript.append_serializable(b"outputs", &outputs.to_vec());
+        let mut point: Vec<F> = (0..num_vars).map(|_| transcript.challenge_scalar(b"output_point")).collect();
+        let mut claim = to_mle(outputs, num_vars).evaluate(&point).unwrap();
+
+        for (i, (layer, layer_proof)) in circuit.layers.iter().zip(&proof.layers).enumerate() {
+            let next_vars = circuit.num_vars(i + 1);
+            if layer_proof.line_evaluations.len() != next_vars + 1 {
+                return Err(SumcheckError::MalformedProof("wrong number of line evaluations"));
+            }
+
+            // every variable appears in at most two factors of a term
+            let subclaim = Self::verify(&layer_proof.sumcheck, claim, &vec![2; 2 * next_vars], transcript)?;
+            let (b, c) = subclaim.point.split_at(next_vars);
+
+            // evaluate the wiring predicates at (point, b, c)
+            let mut add = F::zero();
+            let mut mul = F::zero();
+            for (g, gate) in layer.gates.iter().enumerate() {
+                let (left, right) = gate.inputs();
+                let wiring = eq_eval_at_index(g, &point) * eq_eval_at_index(left, b) * eq_eval_at_index(right, c);
+                match gate {
+                    Gate::Add(..) => add += wiring,
+                    Gate::Mul(..) => mul += wiring,
+                }
+            }
+            let w_b = layer_proof.line_evaluations[0];
+            let w_c = layer_proof.line_evaluations[1];
+            if add * (w_b + w_c) + mul * w_b * w_c != subclaim.expected_evaluation {
+                return Err(SumcheckError::LayerEvaluationCheckFailed { layer: i });
+            }
+
+            // reduce the two claims about the next layer to one
+            transcript.append_serializable(b"line_evaluations", &layer_proof.line_evaluations);
+            let t = transcript.challenge_scalar(b"line_challenge");
+            point = line_point(b, c, t);
+            claim = interpolate_uni_poly(&layer_proof.line_evaluations).evaluate(&t);
+        }
+
+        let input_vars = circuit.num_vars(circuit.layers.len());
+        if to_mle(inputs, input_vars).evaluate(&point).unwrap() == claim {
+            Ok(())
+        } else {
+            Err(SumcheckError::InputEvaluationCheckFailed)
+        }
+    }
+}
+
+// sum over c of the wiring polynomial as a polynomial in b:
+// `W(b) Σ_c add(b, c) + Σ_c add(b, c) W(c) + W(b) Σ_c mul(b, c) W(c)`
+fn wiring_over_b<F: Field>(
+    layer: &Layer,
+    eq: &[F],
+    next: &DenseMultilinearExtension<F>
+) -> VirtualPolynomial<F> {
+    let size = next.evaluations.len();
+    let mut add = vec![F::zero(); size];
+    let mut add_w = vec![F::zero(); size];
+    let mut mul_w = vec![F::zero(); size];
+    for (g, gate) in layer.gates.iter().enumerate() {
+        let (left, right) = gate.inputs();
+        match gate {
+            Gate::Add(..) => {
+                add[left] += eq[g];
+                add_w[left] += eq[g] * next.evaluations[right];
+            },
+            Gate::Mul(..) => mul_w[left] += eq[g] * next.evaluations[right],
+        }
+    }
+    VirtualPolynomial::new(
+        vec![
+            DenseMultilinearExtension::from_evaluations_vec(next.num_vars, add),
+            DenseMultilinearExtension::from_evaluations_vec(next.num_vars, add_w),
+            DenseMultilinearExtension::from_evaluations_vec(next.num_vars, mul_w),
+            next.clone(),
+        ],
+        vec![
+            (F::one(), vec![0, 3]),
+            (F::one(), vec![1]),
+            (F::one(), vec![2, 3]),
+        ],
+    )
+}
+
+// wiring polynomial with b fixed, as a polynomial in c:
+// `add(b, c) (W(b) + W(c)) + mul(b, c) W(b) W(c)`
+fn wiring_over_c<F: Field>(
+    layer: &Layer,
+    eq: &[F],
+    next: &DenseMultilinearExtension<F>,
+    b: &[F]
+) -> VirtualPolynomial<F> {
+    let eq_b = eq_table(b);
+    let w_b = next.evaluate(b).unwrap();
+    let size = next.evaluations.len();
+    let mut add = vec![F::zero(); size];
+    let mut mul = vec![F::zero(); size];
+    for (g, gate) in layer.gates.iter().enumerate() {
+        let (left, right) = gate.inputs();
+        match gate {
+            Gate::Add(..) => add[right] += eq[g] * eq_b[left],
+            Gate::Mul(..) => mul[right] += eq[g] * eq_b[left],
+        }
+    }
+    VirtualPolynomial::new(
+        vec![
+            DenseMultilinearExtension::from_evaluations_vec(next.num_vars, add),
+            DenseMultilinearExtension::from_evaluations_vec(next.num_vars, mul),
+            next.clone(),
+        ],
+        vec![
+            (w_b, vec![0]),
+            (F::one(), vec![0, 2]),
+            (w_b, vec![1, 2]),
+        ],
+    )
+}
+
+// point b + t (c - b) on the line through b and c
+fn line_point<F: Field>(b: &[F], c: &[F], t: F) -> Vec<F> {
+    b.iter().zip(c).map(|(&b, &c)| b + t * (c - b)).collect()
+}
+
+fn to_mle<F: Field>(values: &[F], num_vars: usize) -> DenseMultilinearExtension<F> {
+    let mut evaluations = values.to_vec();
+    evaluations.resize(1 << num_vars, F::zero());
+    DenseMultilinearExtension::from_evaluations_vec(num_vars, evaluations)
+}
+
+// at least one variable, so that every layer goes through a sumcheck round
+fn log_size(width: usize) -> usize {
+    (width.next_power_of_two().trailing_zeros() as usize).max(1)
+}
@@ -0,0 +1,127 @@
+//! Grand product argument (Thaler, 2013; Lasso, section 5).
+//!
+//! The product of 2^n leaves is computed by a binary tree whose layer i holds 2^i
+//! values, node g of layer i being the product of nodes 2g and 2g + 1 of layer i + 1.
+//! With V_i the multilinear extension of layer i and the child selected by the first
+//! variable, `V_i(z) = Σ_x eq(z, x) V_{i+1}(0, x) V_{i+1}(1, x)`, so a claim about V_i
+//! is reduced by a degree-3 sumcheck to the claims V_{i+1}(0, r) and V_{i+1}(1, r),
+//! which the line through them turns into one claim about V_{i+1}. V_0 has no
+//! variables, so the first layer is reduced without a sumcheck from the two halves'
+//! products. Starting from the product V_0, the verifier ends with a claim about the
+//! leaf MLE, left to the caller.
+
+use ark_poly::{DenseMultilinearExtension, MultilinearExtension};
+use ark_ff::Field;
+use ark_serialize::{CanonicalSerialize, CanonicalDeserialize};
+use crate::IPForSumCheck;
+use crate::error::SumcheckError;
+use crate::proof::SumcheckProof;
+use crate::transcript::Transcript;
+use crate::utils::{eq_eval, eq_table};
+use crate::verifier::SubClaim;
+use crate::virtual_poly::VirtualPolynomial;
+
+/// messages of the prover for the reduction of one layer to the next
+#[derive(Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct GrandProductLayerProof<F: Field> {
+    /// sumcheck of eq(z, x) V_{i+1}(0, x) V_{i+1}(1, x)
+    pub sumcheck: SumcheckProof<F>,
+    /// V_{i+1}(0, r) and V_{i+1}(1, r) at the point of the sumcheck
+    pub child_evaluations: [F; 2],
+}
+
+#[derive(Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct GrandProductProof<F: Field> {
+    /// products of the two halves of the leaves, the values of layer 1
+    pub halves: [F; 2],
+    /// one reduction per layer below the first one
+    pub layers: Vec<GrandProductLayerProof<F>>,
+}
+
+impl<F: Field> IPForSumCheck<F> {
+    // product of the leaves, whose number must be a power of two, and its proof
+    pub fn grand_product_prove(
+        leaves: &[F],
+        transcript: &mut Transcript
+    ) -> (F, GrandProductProof<F>) {
+        assert!(leaves.len().is_power_of_two() && leaves.len() > 1, "number of leaves must be a power of two, at least two");
+        let num_vars = leaves.len().trailing_zeros() as usize;
+
+        // layers from the product down to the leaves
+        let mut tree = vec![leaves.to_vec()];
+        while tree.last().unwrap().len() > 1 {
+            let layer = tree.last().unwrap().chunks(2).map(|pair| pair[0] * pair[1]).collect();
+            tree.push(layer);
+        }
+        tree.reverse();
+        let product = tree[0][0];
+        transcript.append_serializable(b"grand_product", &product);
+        transcript.append_serializable(b"grand_product_num_vars", &(num_vars as u64));
+
+        let halves = [tree[1][0], tree[1][1]];
+        let mut point = child_point(&halves, &[], transcript);
+        let mut layers = Vec::with_capacity(num_vars - 1);
+        for (children, i) in tree[2..].iter().zip(1..) {
+            let left = children.iter().step_by(2).cloned().collect();
+            let right = children.iter().skip(1).step_by(2).cloned().collect();
+            let polynomial = VirtualPolynomial::new(
+                vec![
+                    DenseMultilinearExtension::from_evaluations_vec(i, eq_table(&point)),
+                    DenseMultilinearExtension::from_evaluations_vec(i, left),
+                    DenseMultilinearExtension::from_evaluations_vec(i, right),
+                ],
+                vec![(F::one(), vec![0, 1, 2])],
+            );
+            let (sumcheck, randomness) = Self::vp_prove_with_randomness(&polynomial, transcript);
+            let child_evaluations = [0, 1].map(|mle| polynomial.mles[mle + 1].evaluate(&randomness).unwrap());
+            point = child_point(&child_evaluations, &randomness, transcript);
+            layers.push(GrandProductLayerProof { sumcheck, child_evaluations });
+        }
+
+        (product, GrandProductProof { halves, layers })
+    }
+
+    // check the claimed product of 2^num_vars leaves and return the claim about the
+    // leaf MLE
+    pub fn grand_product_verify(
+        product: F,
+        num_vars: usize,
+        proof: &GrandProductProof<F>,
+        transcript: &mut Transcript
+    ) -> Result<SubClaim<F>, SumcheckError> {
+        if num_vars == 0 || proof.layers.len() != num_vars - 1 {
+            return Err(SumcheckError::MalformedProof("wrong number of layer proofs"));
+        }
+        transcript.append_serializable(b"grand_product", &product);
+        transcript.append_serializable(b"grand_product_num_vars", &(num_vars as u64));
+
+        let [left, right] = proof.halves;
+        if left * right != product {
+            return Err(SumcheckError::LayerEvaluationCheckFailed { layer: 0 });
+        }
+        let mut point = child_point(&proof.halves, &[], transcript);
+        let mut claim = left + point[0] * (right - left);
+        for (layer_proof, i) in proof.layers.iter().zip(1..) {
+            let subclaim = Self::verify(&layer_proof.sumcheck, claim, &vec![3; i], transcript)?;
+            let [left, right] = layer_proof.child_evaluations;
+            if eq_eval(&point, &subclaim.point) * left * right != subclaim.expected_evaluation {
+                return Err(SumcheckError::LayerEvaluationCheckFailed { layer: i });
+            }
+
+            // reduce the two claims about the children to one
+            point = child_point(&layer_proof.child_evaluations, &subclaim.point, transcript);
+            claim = left + point[0] * (right - left);
+        }
+
+        Ok(SubClaim { point, expected_evaluation: claim })
+    }
+}
+
+// absorb the evaluations of the children, then derive the point of the next layer
+// on the line through them, the child variable coming first
+fn child_point<F: Field>(child_evaluations: &[F; 2], point: &[F], transcript: &mut Transcript) -> Vec<F> {
+    transcript.append_serializable(b"child_evaluations", &child_evaluations.to_vec());
+    let mut child = vec![transcript.challenge_scalar(b"child_challenge")];
+    child.extend_from_slice(point);
+    child
+}
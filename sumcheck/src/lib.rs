@@ -1,5 +1,43 @@
 mod prover;
 mod verifier;
+mod ml_prover;
+mod streaming;
+mod proof;
+mod utils;
+mod error;
+pub mod gkr;
+pub mod grand_product;
+pub mod oracle;
+pub mod zk;
+pub mod batched;
+pub mod sat;
+pub mod spartan;
+pub mod triangles;
+pub mod matmult;
+pub mod memory;
+pub mod transcript;
+pub mod virtual_poly;
+pub mod zerocheck;
+
+pub use prover::{ProverMsg, ProverState};
+pub use ml_prover::MLProverState;
+pub use streaming::StreamingProverState;
+pub use verifier::{variable_degrees, SubClaim, VerifierMsg, VerifierState};
+pub use error::SumcheckError;
+pub use proof::SumcheckProof;
+pub use transcript::Transcript;
+pub use virtual_poly::{VirtualPolynomial, VPAuxInfo};
+pub use oracle::{CommittedOracle, EvaluationOracle};
+pub use zk::ZKSumcheckProof;
+pub use batched::BatchedSumcheckProof;
+pub use sat::CnfFormula;
+pub use spartan::{R1CS, SpartanProof};
+pub use triangles::Graph;
+pub use matmult::{matrix_mle, MatMultProof};
+pub use memory::{MemoryAccess, MemoryCheckingProof, MemoryOp, MemoryTrace};
+pub use gkr::{Gate, Layer, LayeredCircuit, GKRProof};
+pub use grand_product::GrandProductProof;
+pub use utils::{eq_eval, eq_eval_at_index, eq_table};
 
 use ark_ff::Field;
 use ark_std::marker::PhantomData;
@@ -13,9 +51,11 @@ pub struct IPForSumCheck<F: Field>{
 #[cfg(test)]
 mod tests {
     use super::*;
-    use ark_poly::multivariate::SparsePolynomial;
-    use ark_poly::DenseMVPolynomial;
-    use ark_std::test_rng;
+    use ark_poly::multivariate::{SparsePolynomial, SparseTerm, Term};
+    use ark_poly::{DenseMVPolynomial, DenseMultilinearExtension, MultilinearExtension, Polynomial};
+    use ark_poly::{univariate::DensePolynomial, DenseUVPolynomial};
+    use ark_ff::{Field, Zero};
+    use ark_std::{rand::Rng, test_rng, UniformRand};
     use ark_bls12_381::Fr;
 
 
@@ -27,7 +67,7 @@ mod tests {
         // where each coefficient is sampled uniformly at random.
         let d = 1;
         let l = 10;
-        let polynomial = SparsePolynomial::rand(d, l.clone(), &mut rng);
+        let polynomial = SparsePolynomial::rand(d, l, &mut rng);
 
         let mut prover_state = IPForSumCheck::prover_init(&polynomial);
         let claimed_value: Fr = IPForSumCheck::run_init_prover_round(&mut prover_state);
@@ -49,5 +89,1128 @@ mod tests {
         assert!(IPForSumCheck::verify_last_round(&mut verifier_state, &polynomial).is_ok());
     }
 
-    
+    #[test]
+    fn test_non_interactive_protocol() {
+        let mut rng = test_rng();
+
+        let d = 3;
+        let l = 5;
+        let polynomial = SparsePolynomial::rand(d, l, &mut rng);
+
+        // the claimed sum is the first value absorbed by the transcript
+        let mut prover_state = IPForSumCheck::prover_init(&polynomial);
+        let claimed_value: Fr = IPForSumCheck::run_init_prover_round(&mut prover_state);
+        let proof = IPForSumCheck::prove(&polynomial, &mut Transcript::new(b"test"));
+        assert_eq!(proof.claimed_value, claimed_value);
+        assert_eq!(proof.round_evaluations.len(), l);
+
+        // the verifier replays the transcript and checks the final claim itself
+        let degrees = variable_degrees(&polynomial);
+        let subclaim = IPForSumCheck::verify(&proof, claimed_value, &degrees, &mut Transcript::new(b"test")).unwrap();
+        assert_eq!(polynomial.evaluate(&subclaim.point), subclaim.expected_evaluation);
+
+        // a wrong claimed sum is rejected
+        let wrong_value = claimed_value + Fr::from(1u64);
+        assert_eq!(
+            IPForSumCheck::verify(&proof, wrong_value, &degrees, &mut Transcript::new(b"test")).err(),
+            Some(SumcheckError::ClaimedValueMismatch)
+        );
+
+        // and a proof rewritten for it leads to a wrong final claim
+        let mut wrong_proof = proof.clone();
+        wrong_proof.claimed_value = wrong_value;
+        let subclaim = IPForSumCheck::verify(&wrong_proof, wrong_value, &degrees, &mut Transcript::new(b"test")).unwrap();
+        assert_ne!(polynomial.evaluate(&subclaim.point), subclaim.expected_evaluation);
+
+        // replaying under another domain separator leads to different challenges
+        let subclaim = IPForSumCheck::verify(&proof, claimed_value, &degrees, &mut Transcript::new(b"other")).unwrap();
+        assert_ne!(polynomial.evaluate(&subclaim.point), subclaim.expected_evaluation);
+    }
+
+    // evaluations of a multilinear polynomial over the hypercube, variable i being bit i of the index
+    fn to_evaluations(polynomial: &SparsePolynomial<Fr, SparseTerm>) -> DenseMultilinearExtension<Fr> {
+        let num_vars = polynomial.num_vars();
+        let evaluations = (0..1usize << num_vars).map(|i| {
+            let point: Vec<Fr> = (0..num_vars).map(|j| Fr::from(((i >> j) & 1) as u64)).collect();
+            polynomial.evaluate(&point)
+        }).collect();
+        DenseMultilinearExtension::from_evaluations_vec(num_vars, evaluations)
+    }
+
+    #[test]
+    fn test_ml_prover_matches_sparse_prover() {
+        let mut rng = test_rng();
+
+        let l = 8;
+        let polynomial = SparsePolynomial::rand(1, l, &mut rng);
+        let evaluations = to_evaluations(&polynomial);
+
+        // both provers claim the same sum and send the same round polynomials
+        let mut prover_state = IPForSumCheck::prover_init(&polynomial);
+        let mut ml_prover_state = IPForSumCheck::ml_prover_init(&evaluations);
+        let claimed_value: Fr = IPForSumCheck::run_init_prover_round(&mut prover_state);
+        assert_eq!(claimed_value, IPForSumCheck::ml_run_init_prover_round(&mut ml_prover_state));
+
+        let mut verifier_state = IPForSumCheck::verifier_init(&polynomial, claimed_value);
+        let mut verifier_msg = None;
+        for _ in 0..l {
+            let prover_msg = IPForSumCheck::prove_round(&mut prover_state, &verifier_msg);
+            let ml_prover_msg = IPForSumCheck::ml_prove_round(&mut ml_prover_state, &verifier_msg);
+            assert_eq!(prover_msg.uvpolynomial, ml_prover_msg.uvpolynomial);
+            verifier_msg = Some(IPForSumCheck::verify_round(&mut verifier_state, &ml_prover_msg.uvpolynomial, &mut rng).unwrap());
+        }
+        assert!(IPForSumCheck::verify_last_round(&mut verifier_state, &polynomial).is_ok());
+
+        // the non-interactive proofs are identical as well
+        let proof = IPForSumCheck::prove(&polynomial, &mut Transcript::new(b"test"));
+        let ml_proof = IPForSumCheck::ml_prove(&evaluations, &mut Transcript::new(b"test"));
+        assert_eq!(proof, ml_proof);
+    }
+
+    #[test]
+    fn test_ml_prover_many_variables() {
+        let mut rng = test_rng();
+
+        let l = 16;
+        let polynomial = DenseMultilinearExtension::<Fr>::rand(l, &mut rng);
+        let claimed_value: Fr = polynomial.evaluations.iter().sum();
+
+        let proof = IPForSumCheck::ml_prove(&polynomial, &mut Transcript::new(b"test"));
+        assert_eq!(proof.claimed_value, claimed_value);
+        let subclaim = IPForSumCheck::verify(&proof, claimed_value, &vec![1; l], &mut Transcript::new(b"test")).unwrap();
+        assert_eq!(polynomial.evaluate(&subclaim.point), Some(subclaim.expected_evaluation));
+    }
+
+    // random virtual polynomial f0*f1*f2 + 3*f0 - f1*f2 + 5*f2*f2
+    fn random_virtual_polynomial(num_vars: usize) -> VirtualPolynomial<Fr> {
+        let mut rng = test_rng();
+        let mles = (0..3).map(|_| DenseMultilinearExtension::rand(num_vars, &mut rng)).collect();
+        VirtualPolynomial::new(mles, vec![
+            (Fr::from(1u64), vec![0, 1, 2]),
+            (Fr::from(3u64), vec![0]),
+            (-Fr::from(1u64), vec![1, 2]),
+            (Fr::from(5u64), vec![2, 2]),
+        ])
+    }
+
+    #[test]
+    fn test_virtual_polynomial_protocol() {
+        let mut rng = test_rng();
+
+        let l = 6;
+        let polynomial = random_virtual_polynomial(l);
+        let aux_info = polynomial.aux_info();
+        assert_eq!(aux_info.max_degree, 3);
+
+        // the claimed sum matches a direct sum over the hypercube
+        let mut prover_state = IPForSumCheck::vp_prover_init(&polynomial);
+        let claimed_value = IPForSumCheck::vp_run_init_prover_round(&mut prover_state);
+        let expected: Fr = (0..1usize << l).map(|i| {
+            let point: Vec<Fr> = (0..l).map(|j| Fr::from(((i >> j) & 1) as u64)).collect();
+            polynomial.evaluate(&point)
+        }).sum();
+        assert_eq!(claimed_value, expected);
+
+        let mut verifier_state = IPForSumCheck::vp_verifier_init(&aux_info, claimed_value);
+        let mut verifier_msg = None;
+        for _ in 0..l {
+            let prover_msg = IPForSumCheck::vp_prove_round(&mut prover_state, &verifier_msg);
+            assert_eq!(prover_msg.evaluations.len(), aux_info.max_degree + 1);
+            verifier_msg = Some(IPForSumCheck::vp_verify_round(&mut verifier_state, &aux_info, &prover_msg, &mut rng).unwrap());
+        }
+        assert!(IPForSumCheck::vp_verify_last_round(&mut verifier_state, &polynomial).is_ok());
+
+        // non-interactive version
+        let proof = IPForSumCheck::vp_prove(&polynomial, &mut Transcript::new(b"test"));
+        assert_eq!(proof.claimed_value, claimed_value);
+        let subclaim = IPForSumCheck::verify(&proof, claimed_value, &aux_info.degrees(), &mut Transcript::new(b"test")).unwrap();
+        assert_eq!(polynomial.evaluate(&subclaim.point), subclaim.expected_evaluation);
+        assert!(IPForSumCheck::verify(&proof, claimed_value + Fr::from(1u64), &aux_info.degrees(), &mut Transcript::new(b"test")).is_err());
+    }
+
+    #[test]
+    fn test_virtual_polynomial_single_table_matches_ml_prover() {
+        let mut rng = test_rng();
+
+        // a virtual polynomial with one linear term is an ordinary multilinear sumcheck
+        let mle = DenseMultilinearExtension::<Fr>::rand(5, &mut rng);
+        let polynomial = VirtualPolynomial::new(vec![mle.clone()], vec![(Fr::from(1u64), vec![0])]);
+        let proof = IPForSumCheck::vp_prove(&polynomial, &mut Transcript::new(b"test"));
+        let ml_proof = IPForSumCheck::ml_prove(&mle, &mut Transcript::new(b"test"));
+        assert_eq!(proof, ml_proof);
+    }
+
+    #[test]
+    fn test_gkr_protocol() {
+        // (x0 * x1) * (x2 + x3) and (x0 * x3) + (x1 + x2)
+        let circuit = LayeredCircuit::new(vec![
+            Layer { gates: vec![Gate::Mul(0, 1), Gate::Add(2, 3)] },
+            Layer { gates: vec![Gate::Mul(0, 1), Gate::Add(2, 3), Gate::Mul(0, 3), Gate::Add(1, 2)] },
+        ], 4);
+        let inputs: Vec<Fr> = [2u64, 3, 5, 7].iter().map(|&x| Fr::from(x)).collect();
+
+        let (outputs, proof) = IPForSumCheck::gkr_prove(&circuit, &inputs, &mut Transcript::new(b"test"));
+        assert_eq!(outputs, vec![Fr::from(72u64), Fr::from(22u64)]);
+        // round polynomials have degree 2 in every b and c variable: g(0) and g(2)
+        for layer in &proof.layers {
+            assert!(layer.sumcheck.round_evaluations.iter().all(|evaluations| evaluations.len() <= 2));
+        }
+        assert!(IPForSumCheck::gkr_verify(&circuit, &inputs, &outputs, &proof, &mut Transcript::new(b"test")).is_ok());
+
+        // wrong outputs or inputs are rejected
+        let wrong_outputs = vec![Fr::from(72u64), Fr::from(23u64)];
+        assert!(IPForSumCheck::gkr_verify(&circuit, &inputs, &wrong_outputs, &proof, &mut Transcript::new(b"test")).is_err());
+        let mut wrong_inputs = inputs.clone();
+        wrong_inputs[3] = Fr::from(8u64);
+        assert!(IPForSumCheck::gkr_verify(&circuit, &wrong_inputs, &outputs, &proof, &mut Transcript::new(b"test")).is_err());
+    }
+
+    #[test]
+    fn test_gkr_wide_layer() {
+        // 2^12 gates, whose wiring over (b, c) would take tables of 2^24 entries
+        let n = 1 << 12;
+        let circuit = LayeredCircuit::new(vec![
+            Layer { gates: vec![Gate::Add(0, 1), Gate::Mul(2, n - 1)] },
+            Layer { gates: (0..n).map(|i| if i % 2 == 0 { Gate::Mul(i, (i + 1) % n) } else { Gate::Add(i, (i + 7) % n) }).collect() },
+        ], n);
+        let inputs: Vec<Fr> = (0..n as u64).map(Fr::from).collect();
+
+        let (outputs, proof) = IPForSumCheck::gkr_prove(&circuit, &inputs, &mut Transcript::new(b"test"));
+        assert_eq!(outputs, circuit.evaluate(&inputs)[0]);
+        assert!(IPForSumCheck::gkr_verify(&circuit, &inputs, &outputs, &proof, &mut Transcript::new(b"test")).is_ok());
+    }
+
+    #[test]
+    fn test_gkr_random_circuit() {
+        let mut rng = test_rng();
+
+        // random wiring over layers of different widths
+        let widths = [3, 8, 5, 16];
+        let num_inputs = 11;
+        let layers = widths.iter().enumerate().map(|(i, &width)| {
+            let next = widths.get(i + 1).copied().unwrap_or(num_inputs);
+            let gates = (0..width).map(|_| {
+                let (left, right) = (rng.gen_range(0..next), rng.gen_range(0..next));
+                if rng.gen_bool(0.5) { Gate::Add(left, right) } else { Gate::Mul(left, right) }
+            }).collect();
+            Layer { gates }
+        }).collect();
+        let circuit = LayeredCircuit::new(layers, num_inputs);
+        let inputs: Vec<Fr> = (0..num_inputs).map(|_| Fr::rand(&mut rng)).collect();
+
+        let (outputs, proof) = IPForSumCheck::gkr_prove(&circuit, &inputs, &mut Transcript::new(b"test"));
+        assert_eq!(outputs, circuit.evaluate(&inputs)[0]);
+        assert!(IPForSumCheck::gkr_verify(&circuit, &inputs, &outputs, &proof, &mut Transcript::new(b"test")).is_ok());
+    }
+
+    #[test]
+    fn test_degree_check_stops_false_claim() {
+        let mut rng = test_rng();
+
+        let l = 4;
+        let polynomial = SparsePolynomial::rand(1, l, &mut rng);
+        let mut prover_state = IPForSumCheck::prover_init(&polynomial);
+        let claimed_value: Fr = IPForSumCheck::run_init_prover_round(&mut prover_state);
+        let false_value = claimed_value + Fr::from(1u64);
+
+        // a prover expecting challenges from a small set S pads the honest round
+        // polynomial with a multiple of prod_{a in S} (X - a): the padded polynomial
+        // sums to the false claim and agrees with the honest one on S, so every later
+        // round could be played honestly
+        let challenges: Vec<Fr> = (2..6u64).map(Fr::from).collect();
+        let vanishing = challenges.iter().fold(
+            DensePolynomial::from_coefficients_vec(vec![Fr::from(1u64)]),
+            |acc, &a| &acc * &DensePolynomial::from_coefficients_vec(vec![-a, Fr::from(1u64)])
+        );
+        let shift = vanishing.evaluate(&Fr::zero()) + vanishing.evaluate(&Fr::from(1u64));
+        let scaled = &vanishing * shift.inverse().unwrap();
+        let honest = IPForSumCheck::prove_round(&mut prover_state, &None).uvpolynomial;
+        let forged = &honest + &scaled;
+        assert_eq!(forged.evaluate(&Fr::zero()) + forged.evaluate(&Fr::from(1u64)), false_value);
+        for a in &challenges {
+            assert_eq!(forged.evaluate(a), honest.evaluate(a));
+        }
+
+        let mut verifier_state = IPForSumCheck::verifier_init(&polynomial, false_value);
+        assert_eq!(
+            IPForSumCheck::verify_round(&mut verifier_state, &forged, &mut rng).err(),
+            Some(SumcheckError::DegreeTooHigh { round: 0, degree: challenges.len(), max_degree: 1 })
+        );
+    }
+
+    #[test]
+    fn test_degree_check_rejects_padded_proof() {
+        let mut rng = test_rng();
+
+        let l = 5;
+        let polynomial = SparsePolynomial::rand(2, l, &mut rng);
+        let mut prover_state = IPForSumCheck::prover_init(&polynomial);
+        let claimed_value: Fr = IPForSumCheck::run_init_prover_round(&mut prover_state);
+        let degrees = variable_degrees(&polynomial);
+
+        // an extra evaluation off the honest round polynomial raises its degree while
+        // keeping g(0) + g(1)
+        let mut proof = IPForSumCheck::prove(&polynomial, &mut Transcript::new(b"test"));
+        assert_eq!(proof.claimed_value, claimed_value);
+        let round = 2;
+        assert!(degrees[round] > 0);
+        proof.round_evaluations[round].push(Fr::from(1u64));
+        assert_eq!(
+            IPForSumCheck::verify(&proof, claimed_value, &degrees, &mut Transcript::new(b"test")).err(),
+            Some(SumcheckError::DegreeTooHigh { round, degree: degrees[round] + 1, max_degree: degrees[round] })
+        );
+
+        // dropping a round is rejected as well
+        let mut proof = IPForSumCheck::prove(&polynomial, &mut Transcript::new(b"test"));
+        proof.round_evaluations.pop();
+        assert_eq!(
+            IPForSumCheck::verify(&proof, claimed_value, &degrees, &mut Transcript::new(b"test")).err(),
+            Some(SumcheckError::WrongNumberOfRounds { expected: l, actual: l - 1 })
+        );
+    }
+
+    #[test]
+    fn test_high_degree_few_variables() {
+        let mut rng = test_rng();
+
+        // per-variable degrees at or above the number of variables
+        for _ in 0..10 {
+            let l = rng.gen_range(1..=3);
+            let d = rng.gen_range(l..=8);
+            let polynomial = SparsePolynomial::rand(d, l, &mut rng);
+
+            let mut prover_state = IPForSumCheck::prover_init(&polynomial);
+            let claimed_value: Fr = IPForSumCheck::run_init_prover_round(&mut prover_state);
+            let mut verifier_state = IPForSumCheck::verifier_init(&polynomial, claimed_value);
+            let mut verifier_msg = None;
+            for _ in 0..l {
+                let prover_msg = IPForSumCheck::prove_round(&mut prover_state, &verifier_msg);
+                verifier_msg = Some(IPForSumCheck::verify_round(&mut verifier_state, &prover_msg.uvpolynomial, &mut rng).unwrap());
+            }
+            assert!(IPForSumCheck::verify_last_round(&mut verifier_state, &polynomial).is_ok());
+
+            let proof = IPForSumCheck::prove(&polynomial, &mut Transcript::new(b"test"));
+            let subclaim = IPForSumCheck::verify(&proof, claimed_value, &variable_degrees(&polynomial), &mut Transcript::new(b"test")).unwrap();
+            assert_eq!(polynomial.evaluate(&subclaim.point), subclaim.expected_evaluation);
+        }
+    }
+
+    #[test]
+    fn test_degree_five_in_three_variables() {
+        let mut rng = test_rng();
+
+        // 3 x0^5 + x0^2 x1^4 x2 + 7 x2^5 + 2
+        let polynomial = SparsePolynomial::from_coefficients_vec(3, vec![
+            (Fr::from(3u64), SparseTerm::new(vec![(0, 5)])),
+            (Fr::from(1u64), SparseTerm::new(vec![(0, 2), (1, 4), (2, 1)])),
+            (Fr::from(7u64), SparseTerm::new(vec![(2, 5)])),
+            (Fr::from(2u64), SparseTerm::new(vec![])),
+        ]);
+        assert_eq!(variable_degrees(&polynomial), vec![5, 4, 5]);
+
+        let mut prover_state = IPForSumCheck::prover_init(&polynomial);
+        let claimed_value: Fr = IPForSumCheck::run_init_prover_round(&mut prover_state);
+        // 4 * (3 + 7) + 1 + 8 * 2
+        assert_eq!(claimed_value, Fr::from(57u64));
+        let mut verifier_state = IPForSumCheck::verifier_init(&polynomial, claimed_value);
+        let mut verifier_msg = None;
+        for round in 0..3 {
+            let prover_msg = IPForSumCheck::prove_round(&mut prover_state, &verifier_msg);
+            assert_eq!(prover_msg.uvpolynomial.degree(), [5, 4, 5][round]);
+            verifier_msg = Some(IPForSumCheck::verify_round(&mut verifier_state, &prover_msg.uvpolynomial, &mut rng).unwrap());
+        }
+        assert!(IPForSumCheck::verify_last_round(&mut verifier_state, &polynomial).is_ok());
+    }
+
+    #[test]
+    fn test_committed_oracle() {
+        use ark_bls12_381::{Bls12_381, G1Projective as G1, G2Projective as G2};
+        use kzg::multilinear::MultilinearKZG;
+
+        let mut rng = test_rng();
+        let l = 5;
+
+        let mut pcs = MultilinearKZG::<Bls12_381>::new(G1::rand(&mut rng), G2::rand(&mut rng), l);
+        let secrets: Vec<Fr> = (0..l).map(|_| Fr::rand(&mut rng)).collect();
+        pcs.setup(&secrets);
+
+        // the verifier only holds the commitment to the polynomial
+        let polynomial = DenseMultilinearExtension::<Fr>::rand(l, &mut rng);
+        let commitment = pcs.commit(&polynomial.evaluations);
+
+        // interactive: the prover opens at the challenges once all rounds are done
+        let mut prover_state = IPForSumCheck::ml_prover_init(&polynomial);
+        let claimed_value = IPForSumCheck::ml_run_init_prover_round(&mut prover_state);
+        let mut verifier_state = IPForSumCheck::verifier_init_with_degrees(vec![1; l], claimed_value);
+        let mut verifier_msg = None;
+        let mut point = vec![];
+        for _ in 0..l {
+            let prover_msg = IPForSumCheck::ml_prove_round(&mut prover_state, &verifier_msg);
+            let msg = IPForSumCheck::verify_round(&mut verifier_state, &prover_msg.uvpolynomial, &mut rng).unwrap();
+            point.push(msg.randomn_value);
+            verifier_msg = Some(msg);
+        }
+        let oracle = CommittedOracle { pcs: &pcs, commitment, proof: pcs.open(&polynomial.evaluations, &point) };
+        assert!(IPForSumCheck::verify_last_round_with_oracle(&mut verifier_state, &oracle).is_ok());
+
+        // non-interactive: the prover replays the transcript to find the point
+        let proof = IPForSumCheck::ml_prove(&polynomial, &mut Transcript::new(b"test"));
+        let subclaim = IPForSumCheck::verify(&proof, claimed_value, &vec![1; l], &mut Transcript::new(b"test")).unwrap();
+        let oracle = CommittedOracle { pcs: &pcs, commitment, proof: pcs.open(&polynomial.evaluations, &subclaim.point) };
+        assert!(IPForSumCheck::verify_with_oracle(&proof, claimed_value, &vec![1; l], &mut Transcript::new(b"test"), &oracle).is_ok());
+
+        // a commitment to another polynomial does not settle the claim
+        let other = DenseMultilinearExtension::<Fr>::rand(l, &mut rng);
+        let oracle = CommittedOracle { pcs: &pcs, commitment: pcs.commit(&other.evaluations), proof: pcs.open(&other.evaluations, &subclaim.point) };
+        assert_eq!(
+            IPForSumCheck::verify_with_oracle(&proof, claimed_value, &vec![1; l], &mut Transcript::new(b"test"), &oracle),
+            Err(SumcheckError::FinalEvaluationCheckFailed)
+        );
+    }
+
+    #[test]
+    fn test_zk_sumcheck() {
+        use ark_bls12_381::{Bls12_381, G1Projective as G1, G2Projective as G2};
+        use kzg::multilinear::MultilinearKZG;
+
+        let mut rng = test_rng();
+        let l = 4;
+
+        let mut pcs = MultilinearKZG::<Bls12_381>::new(G1::rand(&mut rng), G2::rand(&mut rng), l);
+        let secrets: Vec<Fr> = (0..l).map(|_| Fr::rand(&mut rng)).collect();
+        pcs.setup(&secrets);
+
+        let polynomial = DenseMultilinearExtension::<Fr>::rand(l, &mut rng);
+        let commitment = pcs.commit(&polynomial.evaluations);
+        let claimed_value: Fr = polynomial.evaluations.iter().sum();
+
+        let proof = IPForSumCheck::zk_prove(&polynomial, &pcs, commitment, &mut Transcript::new(b"test"), &mut rng);
+        assert!(IPForSumCheck::zk_verify(&proof, claimed_value, &pcs, commitment, &mut Transcript::new(b"test")).is_ok());
+        assert!(IPForSumCheck::zk_verify(&proof, claimed_value + Fr::from(1u64), &pcs, commitment, &mut Transcript::new(b"test")).is_err());
+
+        // the challenges depend on the commitment, so the proof does not carry over to another one
+        let other = DenseMultilinearExtension::<Fr>::rand(l, &mut rng);
+        let other_commitment = pcs.commit(&other.evaluations);
+        let other_value: Fr = other.evaluations.iter().sum();
+        assert!(IPForSumCheck::zk_verify(&proof, other_value, &pcs, other_commitment, &mut Transcript::new(b"test")).is_err());
+    }
+
+    #[test]
+    fn test_zk_proof_is_simulatable() {
+        use ark_bls12_381::{Bls12_381, G1Projective as G1, G2Projective as G2};
+        use kzg::multilinear::MultilinearKZG;
+
+        let mut rng = test_rng();
+        let l = 4;
+
+        let mut pcs = MultilinearKZG::<Bls12_381>::new(G1::rand(&mut rng), G2::rand(&mut rng), l);
+        let secrets: Vec<Fr> = (0..l).map(|_| Fr::rand(&mut rng)).collect();
+        pcs.setup(&secrets);
+
+        // two secret polynomials with the same sum
+        let first = DenseMultilinearExtension::<Fr>::rand(l, &mut rng);
+        let mut second = DenseMultilinearExtension::<Fr>::rand(l, &mut rng);
+        let difference: Fr = first.evaluations.iter().sum::<Fr>() - second.evaluations.iter().sum::<Fr>();
+        second.evaluations[0] += difference;
+        let claimed_value: Fr = first.evaluations.iter().sum();
+
+        // the simulator only knows the claim and rho: it samples the masked polynomial
+        // uniformly, derives the mask sum from it and plays the honest prover
+        let rho = Fr::rand(&mut rng);
+        let simulated = DenseMultilinearExtension::<Fr>::rand(l, &mut rng);
+        let simulated_sum: Fr = simulated.evaluations.iter().sum();
+        let simulated_mask_sum = (simulated_sum - claimed_value) * rho.inverse().unwrap();
+        let challenges: Vec<Fr> = (0..l).map(|_| Fr::rand(&mut rng)).collect();
+        let simulated_view = zk_view(IPForSumCheck::ml_prover_init(&simulated), &challenges);
+        let simulated_opening = pcs.open(&simulated.evaluations, &challenges);
+
+        // for either secret, the uniformly distributed mask (simulated - g) / rho gives
+        // the prover exactly the simulated view, its masked polynomial and so its final
+        // opening are the simulated ones, and its commitment is what the simulator
+        // derives from the commitment to g alone
+        for secret in [&first, &second] {
+            let mask_evaluations = simulated.evaluations.iter()
+                .zip(&secret.evaluations)
+                .map(|(&s, &g)| (s - g) * rho.inverse().unwrap())
+                .collect();
+            let mask = DenseMultilinearExtension::from_evaluations_vec(l, mask_evaluations);
+            let mask_sum: Fr = mask.evaluations.iter().sum();
+            assert_eq!(mask_sum, simulated_mask_sum);
+            assert_eq!(zk_view(IPForSumCheck::zk_prover_init(secret, &mask, rho), &challenges), simulated_view);
+
+            let commitment = pcs.commit(&secret.evaluations);
+            let mask_commitment = (pcs.commit(&simulated.evaluations) - commitment) * rho.inverse().unwrap();
+            assert_eq!(pcs.commit(&mask.evaluations), mask_commitment);
+        }
+
+        // and the simulated view and opening are accepted by the verifier
+        let mut verifier_state = IPForSumCheck::zk_verifier_init(l, claimed_value, simulated_mask_sum, rho);
+        for (polynomial, &r) in simulated_view.iter().zip(&challenges) {
+            IPForSumCheck::check_round(&verifier_state, polynomial).unwrap();
+            IPForSumCheck::accept_round(&mut verifier_state, polynomial, r);
+        }
+        let subclaim = IPForSumCheck::subclaim(&verifier_state).unwrap();
+        let commitment = pcs.commit(&first.evaluations);
+        let mask_commitment = (pcs.commit(&simulated.evaluations) - commitment) * rho.inverse().unwrap();
+        assert!(pcs.verify(&subclaim.point, subclaim.expected_evaluation, commitment + mask_commitment * rho, &simulated_opening));
+    }
+
+    // round polynomials sent by an ml prover for fixed challenges
+    fn zk_view(mut prover_state: MLProverState<Fr>, challenges: &[Fr]) -> Vec<DensePolynomial<Fr>> {
+        let mut verifier_msg = None;
+        let mut view = vec![];
+        for &r in challenges {
+            view.push(IPForSumCheck::ml_prove_round(&mut prover_state, &verifier_msg).uvpolynomial);
+            verifier_msg = Some(VerifierMsg { randomn_value: r });
+        }
+        view
+    }
+
+    #[test]
+    fn test_proof_serialization() {
+        use ark_serialize::{CanonicalSerialize, CanonicalDeserialize};
+
+        let mut rng = test_rng();
+        let polynomial = random_virtual_polynomial(5);
+        let aux_info = polynomial.aux_info();
+        let proof = IPForSumCheck::vp_prove(&polynomial, &mut Transcript::new(b"test"));
+
+        // the evaluation at 1 is left out of every round message
+        assert!(proof.round_evaluations.iter().all(|evaluations| evaluations.len() == aux_info.max_degree));
+
+        let mut bytes = vec![];
+        proof.serialize_compressed(&mut bytes).unwrap();
+        assert_eq!(bytes.len(), proof.compressed_size());
+        let decoded = SumcheckProof::<Fr>::deserialize_compressed(bytes.as_slice()).unwrap();
+        assert_eq!(decoded, proof);
+        let subclaim = IPForSumCheck::verify(&decoded, proof.claimed_value, &aux_info.degrees(), &mut Transcript::new(b"test")).unwrap();
+        assert_eq!(polynomial.evaluate(&subclaim.point), subclaim.expected_evaluation);
+
+        // round messages serialize as well
+        let mut prover_state = IPForSumCheck::prover_init(&SparsePolynomial::<Fr, SparseTerm>::rand(2, 3, &mut rng));
+        let prover_msg = IPForSumCheck::prove_round(&mut prover_state, &None);
+        let mut bytes = vec![];
+        prover_msg.serialize_compressed(&mut bytes).unwrap();
+        let decoded = ProverMsg::<Fr>::deserialize_compressed(bytes.as_slice()).unwrap();
+        assert_eq!(decoded.uvpolynomial, prover_msg.uvpolynomial);
+    }
+
+    #[test]
+    fn test_state_serialization() {
+        use ark_serialize::{CanonicalSerialize, CanonicalDeserialize};
+
+        let mut rng = test_rng();
+        let l = 6;
+        let polynomial = SparsePolynomial::<Fr, SparseTerm>::rand(3, l, &mut rng);
+        let mut prover_state = IPForSumCheck::prover_init(&polynomial);
+        let claimed_value = IPForSumCheck::run_init_prover_round(&mut prover_state);
+        let mut verifier_state = IPForSumCheck::verifier_init(&polynomial, claimed_value);
+        let mut verifier_msg = None;
+        for round in 0..l {
+            // both parties can be stopped and resumed between rounds
+            if round == l / 2 {
+                let mut bytes = vec![];
+                prover_state.serialize_compressed(&mut bytes).unwrap();
+                prover_state = ProverState::deserialize_compressed(bytes.as_slice()).unwrap();
+                let mut bytes = vec![];
+                verifier_state.serialize_compressed(&mut bytes).unwrap();
+                verifier_state = VerifierState::deserialize_compressed(bytes.as_slice()).unwrap();
+            }
+            let prover_msg = IPForSumCheck::prove_round(&mut prover_state, &verifier_msg);
+            verifier_msg = Some(IPForSumCheck::verify_round(&mut verifier_state, &prover_msg.uvpolynomial, &mut rng).unwrap());
+        }
+        assert!(IPForSumCheck::verify_last_round(&mut verifier_state, &polynomial).is_ok());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_proof_serde() {
+        let polynomial = random_virtual_polynomial(4);
+        let proof = IPForSumCheck::vp_prove(&polynomial, &mut Transcript::new(b"test"));
+
+        let json = serde_json::to_string(&proof).unwrap();
+        let decoded: SumcheckProof<Fr> = serde_json::from_str(&json).unwrap();
+        assert_eq!(decoded, proof);
+        assert!(IPForSumCheck::verify(&decoded, proof.claimed_value, &polynomial.aux_info().degrees(), &mut Transcript::new(b"test")).is_ok());
+    }
+
+    #[test]
+    fn test_batched_sumcheck() {
+        let mut rng = test_rng();
+
+        // polynomials with 3, 5 and 5 variables and different degrees
+        let polynomials = vec![
+            random_virtual_polynomial(3),
+            random_virtual_polynomial(5),
+            VirtualPolynomial::new(vec![DenseMultilinearExtension::rand(5, &mut rng)], vec![(Fr::from(2u64), vec![0])]),
+        ];
+        let aux_infos: Vec<VPAuxInfo> = polynomials.iter().map(|polynomial| polynomial.aux_info()).collect();
+        let claimed_values: Vec<Fr> = polynomials.iter()
+            .map(|polynomial| IPForSumCheck::vp_run_init_prover_round(&mut IPForSumCheck::vp_prover_init(polynomial)))
+            .collect();
+
+        // a single sumcheck over the largest number of variables
+        let proof = IPForSumCheck::batch_prove(&polynomials, &mut Transcript::new(b"test"));
+        assert_eq!(proof.sumcheck.round_evaluations.len(), 5);
+        let subclaims = IPForSumCheck::batch_verify(&proof, &claimed_values, &aux_infos, &mut Transcript::new(b"test")).unwrap();
+        for (polynomial, subclaim) in polynomials.iter().zip(&subclaims) {
+            assert_eq!(subclaim.point.len(), polynomial.num_vars);
+            assert_eq!(polynomial.evaluate(&subclaim.point), subclaim.expected_evaluation);
+        }
+
+        // a wrong claim is rejected
+        let mut wrong_values = claimed_values.clone();
+        wrong_values[1] += Fr::from(1u64);
+        assert_eq!(
+            IPForSumCheck::batch_verify(&proof, &wrong_values, &aux_infos, &mut Transcript::new(b"test")).err(),
+            Some(SumcheckError::ClaimedValueMismatch)
+        );
+
+        // and so are evaluations inconsistent with the final claim
+        let mut wrong_proof = proof.clone();
+        wrong_proof.evaluations[0] += Fr::from(1u64);
+        assert_eq!(
+            IPForSumCheck::batch_verify(&wrong_proof, &claimed_values, &aux_infos, &mut Transcript::new(b"test")).err(),
+            Some(SumcheckError::FinalEvaluationCheckFailed)
+        );
+    }
+
+    // number of satisfying assignments by enumeration
+    fn count_satisfying(formula: &CnfFormula) -> u64 {
+        (0..1usize << formula.num_vars)
+            .filter(|assignment| formula.clauses.iter().all(|clause| {
+                clause.iter().any(|&literal| ((assignment >> (literal.unsigned_abs() - 1)) & 1 == 1) == (literal > 0))
+            }))
+            .count() as u64
+    }
+
+    #[test]
+    fn test_sat_counting() {
+        let input = "c (x1 v -x2) ^ (x2 v x3 v -x4) ^ (-x1 v x4) ^ (x3 v x3)
+p cnf 5 4
+1 -2 0
+2 3 -4 0
+-1 4
+0
+3 3 0
+";
+        let formula = CnfFormula::parse_dimacs(input).unwrap();
+        assert_eq!(formula.clauses, vec![vec![-2, 1], vec![-4, 2, 3], vec![-1, 4], vec![3]]);
+
+        let proof = IPForSumCheck::<Fr>::sat_prove(&formula, &mut Transcript::new(b"test"));
+        let count = IPForSumCheck::sat_verify(&formula, &proof, &mut Transcript::new(b"test")).unwrap();
+        assert_eq!(count, count_satisfying(&formula));
+
+        // a wrong count is caught at the final evaluation
+        let mut wrong_proof = proof.clone();
+        wrong_proof.claimed_value += Fr::from(1u64);
+        assert!(IPForSumCheck::sat_verify(&formula, &wrong_proof, &mut Transcript::new(b"test")).is_err());
+
+        // the arithmetized formula agrees with the clause tables off the hypercube
+        let mut rng = test_rng();
+        let point: Vec<Fr> = (0..5).map(|_| Fr::rand(&mut rng)).collect();
+        assert_eq!(formula.evaluate(&point), formula.to_virtual_polynomial().evaluate(&point));
+
+        // formulas without variables are rejected instead of reaching the prover
+        assert!(CnfFormula::parse_dimacs("p cnf 0 0\n").is_err());
+        assert!(CnfFormula::new(0, vec![]).is_err());
+    }
+
+    #[test]
+    fn test_sat_from_dimacs_file() {
+        let mut rng = test_rng();
+
+        // random 3-CNF formula written to a file
+        let (num_vars, num_clauses) = (8, 12);
+        let mut input = format!("c random 3-CNF\np cnf {} {}\n", num_vars, num_clauses);
+        for _ in 0..num_clauses {
+            for _ in 0..3 {
+                let variable = rng.gen_range(1..=num_vars as i64);
+                input += &format!("{} ", if rng.gen_bool(0.5) { variable } else { -variable });
+            }
+            input += "0\n";
+        }
+        let path = std::env::temp_dir().join("sumcheck_test_random.cnf");
+        std::fs::write(&path, input).unwrap();
+        let formula = CnfFormula::from_dimacs_file(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let proof = IPForSumCheck::<Fr>::sat_prove(&formula, &mut Transcript::new(b"test"));
+        let count = IPForSumCheck::sat_verify(&formula, &proof, &mut Transcript::new(b"test")).unwrap();
+        assert_eq!(count, count_satisfying(&formula));
+
+        // malformed inputs are rejected
+        assert!(CnfFormula::parse_dimacs("1 2 0\n").is_err());
+        assert!(CnfFormula::parse_dimacs("p cnf 2 1\n1 3 0\n").is_err());
+        assert!(CnfFormula::parse_dimacs("p cnf 2 2\n1 2 0\n").is_err());
+    }
+
+    #[test]
+    fn test_triangle_counting() {
+        // complete graph on 4 vertices, a 5-cycle and two triangles sharing an edge
+        let cases = [
+            (Graph::from_edges(4, &[(0, 1), (0, 2), (0, 3), (1, 2), (1, 3), (2, 3)]).unwrap(), 4),
+            (Graph::from_edges(5, &[(0, 1), (1, 2), (2, 3), (3, 4), (4, 0)]).unwrap(), 0),
+            (Graph::parse_edge_list("# diamond\n0 1\n1 2\n2 0\n1 3\n3 2\n2 2\n").unwrap(), 2),
+        ];
+        for (graph, triangles) in cases {
+            let proof = IPForSumCheck::<Fr>::triangle_prove(&graph, &mut Transcript::new(b"test"));
+            assert_eq!(proof.claimed_value, Fr::from(6 * triangles));
+            assert_eq!(IPForSumCheck::triangle_verify(&graph, &proof, &mut Transcript::new(b"test")), Ok(triangles));
+
+            let mut wrong_proof = proof.clone();
+            wrong_proof.claimed_value += Fr::from(6u64);
+            assert!(IPForSumCheck::triangle_verify(&graph, &wrong_proof, &mut Transcript::new(b"test")).is_err());
+        }
+    }
+
+    #[test]
+    fn test_triangle_counting_from_file() {
+        let mut rng = test_rng();
+
+        // random graph on 12 vertices written as an edge list
+        let num_vertices = 12;
+        let mut input = String::from("# random graph\n");
+        let mut edges = vec![];
+        for u in 0..num_vertices {
+            for v in u + 1..num_vertices {
+                if rng.gen_bool(0.4) {
+                    input += &format!("{} {}\n", u, v);
+                    edges.push((u, v));
+                }
+            }
+        }
+        let path = std::env::temp_dir().join("sumcheck_test_graph.txt");
+        std::fs::write(&path, input).unwrap();
+        let graph = Graph::from_edge_list_file(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let mut triangles = 0;
+        for &(u, v) in &edges {
+            for w in v + 1..num_vertices {
+                if edges.contains(&(u, w)) && edges.contains(&(v, w)) {
+                    triangles += 1;
+                }
+            }
+        }
+        let proof = IPForSumCheck::<Fr>::triangle_prove(&graph, &mut Transcript::new(b"test"));
+        assert_eq!(IPForSumCheck::triangle_verify(&graph, &proof, &mut Transcript::new(b"test")), Ok(triangles));
+    }
+
+    #[test]
+    fn test_matmult() {
+        use ndarray::Array2;
+
+        let mut rng = test_rng();
+
+        // A is 6 x 10 and B is 10 x 3, padded to 8 x 16 and 16 x 4
+        let a = Array2::from_shape_fn((6, 10), |_| Fr::rand(&mut rng));
+        let b = Array2::from_shape_fn((10, 3), |_| Fr::rand(&mut rng));
+        let c = a.dot(&b);
+
+        // the verifier picks a random entry of the extension of C
+        let r1: Vec<Fr> = (0..3).map(|_| Fr::rand(&mut rng)).collect();
+        let r2: Vec<Fr> = (0..2).map(|_| Fr::rand(&mut rng)).collect();
+        let claimed_value = matrix_mle(&c).evaluate(&[r1.clone(), r2.clone()].concat()).unwrap();
+
+        let proof = IPForSumCheck::matmult_prove(&a, &b, &r1, &r2, &mut Transcript::new(b"test"));
+        assert_eq!(proof.sumcheck.claimed_value, claimed_value);
+        assert_eq!(proof.sumcheck.round_evaluations.len(), 4);
+        let (a_claim, b_claim) = IPForSumCheck::matmult_verify(&proof, claimed_value, &r1, &r2, (6, 10, 3), &mut Transcript::new(b"test")).unwrap();
+        assert!(matrix_mle(&a).check_evaluation(&a_claim.point, a_claim.expected_evaluation));
+        assert!(matrix_mle(&b).check_evaluation(&b_claim.point, b_claim.expected_evaluation));
+
+        // a wrong product is caught with high probability at a random entry
+        let mut wrong_c = c.clone();
+        wrong_c[[4, 1]] += Fr::from(1u64);
+        let wrong_value = matrix_mle(&wrong_c).evaluate(&[r1.clone(), r2.clone()].concat()).unwrap();
+        assert_eq!(
+            IPForSumCheck::matmult_verify(&proof, wrong_value, &r1, &r2, (6, 10, 3), &mut Transcript::new(b"test")).err(),
+            Some(SumcheckError::ClaimedValueMismatch)
+        );
+        let mut forged = IPForSumCheck::matmult_prove(&a, &b, &r1, &r2, &mut Transcript::new(b"test"));
+        forged.sumcheck.claimed_value = wrong_value;
+        assert!(IPForSumCheck::matmult_verify(&forged, wrong_value, &r1, &r2, (6, 10, 3), &mut Transcript::new(b"test")).is_err());
+
+        // a point that does not index an entry of C is rejected
+        assert_eq!(
+            IPForSumCheck::matmult_verify(&proof, claimed_value, &r1[1..], &r2, (6, 10, 3), &mut Transcript::new(b"test")).err(),
+            Some(SumcheckError::MalformedProof("point does not index an entry of C"))
+        );
+        let long_r2 = [r2.clone(), vec![Fr::rand(&mut rng)]].concat();
+        assert_eq!(
+            IPForSumCheck::matmult_verify(&proof, claimed_value, &r1, &long_r2, (6, 10, 3), &mut Transcript::new(b"test")).err(),
+            Some(SumcheckError::MalformedProof("point does not index an entry of C"))
+        );
+    }
+
+    #[test]
+    fn test_sum_over_larger_domain() {
+        let mut rng = test_rng();
+
+        for (size, d, l) in [(3, 3, 3), (5, 4, 2), (2, 2, 4)] {
+            let domain: Vec<Fr> = (0..size).map(Fr::from).collect();
+            let polynomial = SparsePolynomial::rand(d, l, &mut rng);
+
+            // the claimed sum runs over H^l
+            let mut prover_state = IPForSumCheck::prover_init_with_domain(&polynomial, &domain);
+            let claimed_value: Fr = IPForSumCheck::run_init_prover_round(&mut prover_state);
+            let expected: Fr = (0..(size as usize).pow(l as u32)).map(|i| {
+                let point: Vec<Fr> = (0..l).map(|j| domain[i / (size as usize).pow(j as u32) % size as usize]).collect();
+                polynomial.evaluate(&point)
+            }).sum();
+            assert_eq!(claimed_value, expected);
+
+            let mut verifier_state = IPForSumCheck::verifier_init_with_domain(&polynomial, claimed_value, &domain);
+            let mut verifier_msg = None;
+            for _ in 0..l {
+                let prover_msg = IPForSumCheck::prove_round(&mut prover_state, &verifier_msg);
+                verifier_msg = Some(IPForSumCheck::verify_round(&mut verifier_state, &prover_msg.uvpolynomial, &mut rng).unwrap());
+            }
+            assert!(IPForSumCheck::verify_last_round(&mut verifier_state, &polynomial).is_ok());
+
+            // non-interactively, every evaluation of the round polynomials is sent
+            let degrees = variable_degrees(&polynomial);
+            let proof = IPForSumCheck::prove_with_domain(&polynomial, &domain, &mut Transcript::new(b"test"));
+            assert_eq!(proof.claimed_value, claimed_value);
+            let subclaim = IPForSumCheck::verify_with_domain(&proof, claimed_value, &degrees, &domain, &mut Transcript::new(b"test")).unwrap();
+            assert_eq!(polynomial.evaluate(&subclaim.point), subclaim.expected_evaluation);
+            if size != 2 {
+                // and the proof is not accepted as a sum over the hypercube
+                assert!(IPForSumCheck::verify(&proof, claimed_value, &degrees, &mut Transcript::new(b"test")).is_err());
+            }
+
+            // the hypercube claim does not pass as a claim over H
+            let mut hypercube_state = IPForSumCheck::prover_init(&polynomial);
+            let hypercube_value: Fr = IPForSumCheck::run_init_prover_round(&mut hypercube_state);
+            if size != 2 {
+                let mut verifier_state = IPForSumCheck::verifier_init_with_domain(&polynomial, hypercube_value, &domain);
+                let prover_msg = IPForSumCheck::prove_round(&mut hypercube_state, &None);
+                assert_eq!(
+                    IPForSumCheck::verify_round(&mut verifier_state, &prover_msg.uvpolynomial, &mut rng).err(),
+                    Some(SumcheckError::FirstEvaluationCheckFailed)
+                );
+            } else {
+                assert_eq!(hypercube_value, claimed_value);
+            }
+        }
+    }
+
+    #[test]
+    fn test_streaming_prover_matches_ml_prover() {
+        let mut rng = test_rng();
+
+        // evaluations generated on the fly, never stored by the streaming prover
+        let l = 10;
+        let stream = || (0..1u64 << l).map(|i| Fr::from(i * i + 7) * Fr::from(i % 13 + 1));
+        let polynomial = DenseMultilinearExtension::from_evaluations_vec(l, stream().collect());
+
+        let proof = IPForSumCheck::streaming_prove(stream, l, &mut Transcript::new(b"test"));
+        assert_eq!(proof, IPForSumCheck::ml_prove(&polynomial, &mut Transcript::new(b"test")));
+        let subclaim = IPForSumCheck::verify(&proof, polynomial.evaluations.iter().sum(), &vec![1; l], &mut Transcript::new(b"test")).unwrap();
+        assert_eq!(polynomial.evaluate(&subclaim.point), Some(subclaim.expected_evaluation));
+
+        // interactive rounds agree as well, including challenges 0 and 1
+        let polynomial = DenseMultilinearExtension::<Fr>::rand(6, &mut rng);
+        let mut ml_state = IPForSumCheck::ml_prover_init(&polynomial);
+        let mut streaming_state = IPForSumCheck::streaming_prover_init(|| polynomial.evaluations.iter().cloned(), 6);
+        assert_eq!(
+            IPForSumCheck::streaming_run_init_prover_round(&mut streaming_state),
+            IPForSumCheck::ml_run_init_prover_round(&mut ml_state)
+        );
+        let challenges = [Fr::rand(&mut rng), Fr::from(0u64), Fr::rand(&mut rng), Fr::from(1u64), Fr::rand(&mut rng)];
+        let mut verifier_msg = None;
+        for i in 0..6 {
+            assert_eq!(
+                IPForSumCheck::streaming_prove_round(&mut streaming_state, &verifier_msg).uvpolynomial,
+                IPForSumCheck::ml_prove_round(&mut ml_state, &verifier_msg).uvpolynomial
+            );
+            verifier_msg = challenges.get(i).map(|&r| VerifierMsg { randomn_value: r });
+        }
+    }
+
+    #[test]
+    fn test_parallel_prover_matches_serial() {
+        let mut rng = test_rng();
+
+        // enough variables for the hypercube sums to span several chunks
+        let l = 10;
+        let polynomial = SparsePolynomial::rand(3, l, &mut rng);
+        let proof = IPForSumCheck::prove(&polynomial, &mut Transcript::new(b"test"));
+        let claimed_value: Fr = (0..1usize << l)
+            .map(|i| polynomial.evaluate(&(0..l).map(|j| Fr::from(((i >> j) & 1) as u64)).collect()))
+            .sum();
+        assert_eq!(proof.claimed_value, claimed_value);
+        let subclaim = IPForSumCheck::verify(&proof, claimed_value, &variable_degrees(&polynomial), &mut Transcript::new(b"test")).unwrap();
+        assert_eq!(polynomial.evaluate(&subclaim.point), subclaim.expected_evaluation);
+
+        // a single thread reproduces the transcript of the default pool
+        #[cfg(feature = "parallel")]
+        {
+            let pool = rayon::ThreadPoolBuilder::new().num_threads(1).build().unwrap();
+            let serial = pool.install(|| IPForSumCheck::prove(&polynomial, &mut Transcript::new(b"test")));
+            assert_eq!(serial, proof);
+        }
+    }
+
+    #[test]
+    fn test_eq_table() {
+        let mut rng = test_rng();
+        let point: Vec<Fr> = (0..5).map(|_| Fr::rand(&mut rng)).collect();
+        let table = eq_table(&point);
+        assert_eq!(table.len(), 32);
+        for (i, &value) in table.iter().enumerate() {
+            assert_eq!(value, eq_eval_at_index(i, &point));
+        }
+        // the table is the multilinear extension of eq(., point)
+        let other: Vec<Fr> = (0..5).map(|_| Fr::rand(&mut rng)).collect();
+        let mle = DenseMultilinearExtension::from_evaluations_vec(5, table);
+        assert_eq!(mle.evaluate(&other), Some(eq_eval(&other, &point)));
+    }
+
+    #[test]
+    fn test_zerocheck() {
+        let mut rng = test_rng();
+
+        // a * b - c vanishes on the hypercube when c is the entrywise product
+        let l = 5;
+        let a = DenseMultilinearExtension::<Fr>::rand(l, &mut rng);
+        let b = DenseMultilinearExtension::<Fr>::rand(l, &mut rng);
+        let product = a.evaluations.iter().zip(&b.evaluations).map(|(&a, &b)| a * b).collect();
+        let c = DenseMultilinearExtension::from_evaluations_vec(l, product);
+        let polynomial = VirtualPolynomial::new(vec![a, b, c.clone()], vec![
+            (Fr::from(1u64), vec![0, 1]),
+            (-Fr::from(1u64), vec![2]),
+        ]);
+        let aux_info = polynomial.aux_info();
+
+        let proof = IPForSumCheck::zerocheck_prove(&polynomial, &mut Transcript::new(b"test"));
+        assert_eq!(proof.round_evaluations[0].len(), aux_info.max_degree + 1);
+        let subclaim = IPForSumCheck::zerocheck_verify(&proof, &aux_info, &mut Transcript::new(b"test")).unwrap();
+        assert_eq!(polynomial.evaluate(&subclaim.point), subclaim.expected_evaluation);
+
+        // a single nonzero value on the hypercube is caught by the claimed sum
+        let mut evaluations = c.evaluations;
+        evaluations[7] += Fr::from(1u64);
+        let mut wrong = polynomial.clone();
+        wrong.mles[2] = DenseMultilinearExtension::from_evaluations_vec(l, evaluations);
+        let proof = IPForSumCheck::zerocheck_prove(&wrong, &mut Transcript::new(b"test"));
+        assert_eq!(
+            IPForSumCheck::zerocheck_verify(&proof, &aux_info, &mut Transcript::new(b"test")),
+            Err(SumcheckError::ClaimedValueMismatch)
+        );
+
+        // claiming 0 anyway leaves a final claim that does not match f
+        let mut forged = proof.clone();
+        forged.claimed_value = Fr::from(0u64);
+        let subclaim = IPForSumCheck::zerocheck_verify(&forged, &aux_info, &mut Transcript::new(b"test")).unwrap();
+        assert_ne!(wrong.evaluate(&subclaim.point), subclaim.expected_evaluation);
+    }
+
+    // x^3 + x + 5 = out, with z = (1, out, x, x^2, x^3, x^3 + x)
+    fn cubic_r1cs() -> R1CS<Fr> {
+        let one = Fr::from(1u64);
+        R1CS::new(4, 1, 4,
+            vec![(0, 2, one), (1, 3, one), (2, 4, one), (2, 2, one), (3, 5, one), (3, 0, Fr::from(5u64))],
+            vec![(0, 2, one), (1, 2, one), (2, 0, one), (3, 0, one)],
+            vec![(0, 3, one), (1, 4, one), (2, 5, one), (3, 1, one)],
+        ).unwrap()
+    }
+
+    #[test]
+    fn test_spartan() {
+        let r1cs = cubic_r1cs();
+        let inputs = [Fr::from(35u64)];
+        let witness = [3u64, 9, 27, 30].map(Fr::from);
+        assert!(r1cs.is_satisfied(&inputs, &witness));
+
+        let proof = IPForSumCheck::spartan_prove(&r1cs, &inputs, &witness, &mut Transcript::new(b"test"));
+        let subclaim = IPForSumCheck::spartan_verify(&r1cs, &inputs, &proof, &mut Transcript::new(b"test")).unwrap();
+        let mut evaluations = witness.to_vec();
+        evaluations.resize(1 << r1cs.witness_vars(), Fr::from(0u64));
+        let witness_mle = DenseMultilinearExtension::from_evaluations_vec(r1cs.witness_vars(), evaluations);
+        assert_eq!(witness_mle.evaluate(&subclaim.point), Some(subclaim.expected_evaluation));
+
+        // other public inputs do not match the proof
+        assert!(IPForSumCheck::spartan_verify(&r1cs, &[Fr::from(36u64)], &proof, &mut Transcript::new(b"test")).is_err());
+
+        // the proof is bound to the instance, even to an entry that does not change it
+        let mut other = r1cs.clone();
+        other.c.push((0, 0, Fr::from(0u64)));
+        assert!(IPForSumCheck::spartan_verify(&other, &inputs, &proof, &mut Transcript::new(b"test")).is_err());
+
+        // entries outside the matrices are rejected by new and by the verifier
+        let one = Fr::from(1u64);
+        assert!(R1CS::new(4, 1, 4, vec![(4, 0, one)], vec![], vec![]).is_err());
+        assert!(R1CS::new(4, 1, 4, vec![], vec![(0, 6, one)], vec![]).is_err());
+        assert!(R1CS::<Fr>::new(0, 1, 4, vec![], vec![], vec![]).is_err());
+        let mut other = r1cs.clone();
+        other.b.push((0, 6, one));
+        assert_eq!(
+            IPForSumCheck::spartan_verify(&other, &inputs, &proof, &mut Transcript::new(b"test")),
+            Err(SumcheckError::MalformedProof("invalid instance"))
+        );
+        let mut other = r1cs.clone();
+        other.a.push((7, 0, one));
+        assert_eq!(
+            IPForSumCheck::spartan_verify(&other, &inputs, &proof, &mut Transcript::new(b"test")),
+            Err(SumcheckError::MalformedProof("invalid instance"))
+        );
+
+        // a wrong witness evaluation is caught by the inner sumcheck
+        let mut wrong = proof.clone();
+        wrong.witness_evaluation += Fr::from(1u64);
+        assert_eq!(
+            IPForSumCheck::spartan_verify(&r1cs, &inputs, &wrong, &mut Transcript::new(b"test")),
+            Err(SumcheckError::FinalEvaluationCheckFailed)
+        );
+    }
+
+    #[test]
+    fn test_spartan_unsatisfied() {
+        let r1cs = cubic_r1cs();
+        let inputs = [Fr::from(35u64)];
+        let witness = [3u64, 9, 28, 31].map(Fr::from);
+        assert!(!r1cs.is_satisfied(&inputs, &witness));
+
+        // the outer zerocheck has a nonzero sum
+        let proof = IPForSumCheck::spartan_prove(&r1cs, &inputs, &witness, &mut Transcript::new(b"test"));
+        assert_eq!(
+            IPForSumCheck::spartan_verify(&r1cs, &inputs, &proof, &mut Transcript::new(b"test")),
+            Err(SumcheckError::ClaimedValueMismatch)
+        );
+
+        // and claiming 0 anyway fails the check of Az Bz - Cz
+        let mut forged = proof.clone();
+        forged.outer.claimed_value = Fr::from(0u64);
+        assert!(IPForSumCheck::spartan_verify(&r1cs, &inputs, &forged, &mut Transcript::new(b"test")).is_err());
+    }
+
+    fn memory_trace() -> MemoryTrace<Fr> {
+        let init = (0..6u64).map(|v| Fr::from(10 * v)).collect();
+        let operations = [
+            MemoryOp::Read(2),
+            MemoryOp::Write(2, Fr::from(7u64)),
+            MemoryOp::Read(2),
+            MemoryOp::Write(5, Fr::from(1u64)),
+            MemoryOp::Read(0),
+            MemoryOp::Write(2, Fr::from(8u64)),
+            MemoryOp::Read(5),
+        ];
+        MemoryTrace::simulate(init, &operations)
+    }
+
+    #[test]
+    fn test_memory_checking() {
+        use ark_serialize::{CanonicalSerialize, CanonicalDeserialize};
+        let trace = memory_trace();
+        assert_eq!(trace.accesses[2].read_value, Fr::from(7u64));
+        assert_eq!(trace.final_timestamps, vec![5, 0, 6, 0, 0, 7]);
+
+        let proof = IPForSumCheck::memory_prove(&trace, &mut Transcript::new(b"test"));
+        assert!(IPForSumCheck::memory_verify(&trace, &proof, &mut Transcript::new(b"test")).is_ok());
+
+        // the proof goes through its canonical encoding unchanged
+        let mut bytes = vec![];
+        proof.serialize_compressed(&mut bytes).unwrap();
+        assert_eq!(MemoryCheckingProof::<Fr>::deserialize_compressed(bytes.as_slice()).unwrap(), proof);
+
+        // a claimed product that the grand product proof does not support
+        let mut wrong = proof.clone();
+        wrong.products[1] += Fr::from(1u64);
+        assert!(IPForSumCheck::memory_verify(&trace, &wrong, &mut Transcript::new(b"test")).is_err());
+    }
+
+    #[test]
+    fn test_memory_checking_reordered_timestamps() {
+        // Write(0, 5) then Read(0), with the read claiming the initial value 0
+        let init = vec![Fr::from(0u64)];
+        let access = |read_value: u64, read_timestamp, write_value: u64| MemoryAccess {
+            address: 0,
+            read_value: Fr::from(read_value),
+            read_timestamp,
+            write_value: Fr::from(write_value),
+        };
+        let forgeries = [
+            // counters taken in the wrong order
+            MemoryTrace {
+                init: init.clone(),
+                accesses: vec![access(0, 1, 5), access(0, 0, 0)],
+                final_values: vec![Fr::from(5u64)],
+                final_timestamps: vec![2],
+            },
+            // the write reads its own tuple, leaving the initial one to the read; the
+            // multisets are equal, only the ordering check catches it
+            MemoryTrace {
+                init,
+                accesses: vec![access(5, 1, 5), access(0, 0, 0)],
+                final_values: vec![Fr::from(0u64)],
+                final_timestamps: vec![2],
+            },
+        ];
+        for trace in forgeries {
+            let proof = IPForSumCheck::memory_prove(&trace, &mut Transcript::new(b"test"));
+            assert_eq!(
+                IPForSumCheck::memory_verify(&trace, &proof, &mut Transcript::new(b"test")),
+                Err(SumcheckError::ReadTimestampNotEarlier { access: 0 })
+            );
+        }
+    }
+
+    #[test]
+    fn test_memory_checking_tampered_traces() {
+        let trace = memory_trace();
+        let mut tampered = vec![];
+
+        // a read returning another value than the last write
+        let mut wrong_value = trace.clone();
+        wrong_value.accesses[2].read_value = Fr::from(9u64);
+        wrong_value.accesses[2].write_value = Fr::from(9u64);
+        tampered.push(wrong_value);
+
+        // a stale read replaying the initial value with its timestamp
+        let mut stale = trace.clone();
+        stale.accesses[2].read_value = Fr::from(20u64);
+        stale.accesses[2].read_timestamp = 0;
+        tampered.push(stale);
+
+        // a final state that does not match the accesses
+        let mut wrong_final = trace.clone();
+        wrong_final.final_values[3] = Fr::from(1u64);
+        tampered.push(wrong_final);
+
+        for trace in tampered {
+            let proof = IPForSumCheck::memory_prove(&trace, &mut Transcript::new(b"test"));
+            assert_eq!(
+                IPForSumCheck::memory_verify(&trace, &proof, &mut Transcript::new(b"test")),
+                Err(SumcheckError::MultisetMismatch)
+            );
+        }
+    }
+
+    #[test]
+    fn test_grand_product() {
+        let mut rng = test_rng();
+
+        for l in [1, 2, 6] {
+            let leaves: Vec<Fr> = (0..1 << l).map(|_| Fr::rand(&mut rng)).collect();
+            let (product, proof) = IPForSumCheck::grand_product_prove(&leaves, &mut Transcript::new(b"test"));
+            assert_eq!(product, leaves.iter().product::<Fr>());
+            assert_eq!(proof.layers.len(), l - 1);
+
+            // the verifier is left with a claim about the leaf MLE
+            let subclaim = IPForSumCheck::grand_product_verify(product, l, &proof, &mut Transcript::new(b"test")).unwrap();
+            let leaf_mle = DenseMultilinearExtension::from_evaluations_vec(l, leaves.clone());
+            assert_eq!(leaf_mle.evaluate(&subclaim.point), Some(subclaim.expected_evaluation));
+
+            // a wrong product does not match the first layer
+            assert!(IPForSumCheck::grand_product_verify(product + Fr::from(1u64), l, &proof, &mut Transcript::new(b"test")).is_err());
+
+            // wrong leaves pass the layers but not the leaf claim
+            let mut wrong_leaves = leaves.clone();
+            wrong_leaves[0] += Fr::from(1u64);
+            let wrong_mle = DenseMultilinearExtension::from_evaluations_vec(l, wrong_leaves);
+            assert_ne!(wrong_mle.evaluate(&subclaim.point), Some(subclaim.expected_evaluation));
+        }
+    }
 }
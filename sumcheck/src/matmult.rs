@@ -0,0 +1,115 @@
+//! Sumcheck-based matrix multiplication (Thaler's MatMult, section 4.4).
+//!
+//! For C = A B and a point (r1, r2), `C(r1, r2) = sum_y A(r1, y) B(y, r2)` over the
+//! multilinear extensions of the matrices. The prover fixes the tables of `A(r1, .)`
+//! and `B(., r2)` in O(n^2) time and runs the sumcheck on their product. The verifier
+//! never touches the matrices: it ends with one evaluation claim for A and one for B,
+//! to be checked directly or through an `EvaluationOracle`.
+
+use ark_poly::{DenseMultilinearExtension, MultilinearExtension};
+use ark_ff::Field;
+use ark_serialize::{CanonicalSerialize, CanonicalDeserialize};
+use ndarray::Array2;
+use crate::IPForSumCheck;
+use crate::error::SumcheckError;
+use crate::proof::SumcheckProof;
+use crate::transcript::Transcript;
+use crate::utils::eq_table;
+use crate::verifier::SubClaim;
+use crate::virtual_poly::VirtualPolynomial;
+
+#[derive(Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct MatMultProof<F: Field> {
+    /// sumcheck for A(r1, y) B(y, r2) over y
+    pub sumcheck: SumcheckProof<F>,
+    /// A(r1, ry) at the point the sumcheck reduced to
+    pub a_evaluation: F,
+    /// B(ry, r2) at the point the sumcheck reduced to
+    pub b_evaluation: F,
+}
+
+// number of variables indexing a dimension, padded to a power of two
+pub fn dimension_vars(dimension: usize) -> usize {
+    (dimension.next_power_of_two().trailing_zeros() as usize).max(1)
+}
+
+// extension of a matrix padded with zeros, the row in the low bits and the column in
+// the high bits of the index
+pub fn matrix_mle<F: Field>(matrix: &Array2<F>) -> DenseMultilinearExtension<F> {
+    let (row_vars, col_vars) = (dimension_vars(matrix.nrows()), dimension_vars(matrix.ncols()));
+    let mut evaluations = vec![F::zero(); 1 << (row_vars + col_vars)];
+    for ((row, col), &entry) in matrix.indexed_iter() {
+        evaluations[row + (col << row_vars)] = entry;
+    }
+    DenseMultilinearExtension::from_evaluations_vec(row_vars + col_vars, evaluations)
+}
+
+impl<F: Field> IPForSumCheck<F> {
+    // prove C(r1, r2) for C = A B, the value being the proof's claimed value
+    pub fn matmult_prove(
+        a: &Array2<F>,
+        b: &Array2<F>,
+        r1: &[F],
+        r2: &[F],
+        transcript: &mut Transcript
+    ) -> MatMultProof<F> {
+        assert_eq!(a.ncols(), b.nrows(), "inner dimensions must agree");
+        assert_eq!(r1.len(), dimension_vars(a.nrows()), "r1 must index the rows of A");
+        assert_eq!(r2.len(), dimension_vars(b.ncols()), "r2 must index the columns of B");
+        transcript.append_serializable(b"matmult_point", &[r1, r2].concat());
+
+        // A(r1, y) = sum_x eq(r1, x) A(x, y) and B(y, r2) = sum_z eq(r2, z) B(y, z)
+        let inner_vars = dimension_vars(a.ncols());
+        let (eq_rows, eq_cols) = (eq_table(r1), eq_table(r2));
+        let mut a_table = vec![F::zero(); 1 << inner_vars];
+        let mut b_table = vec![F::zero(); 1 << inner_vars];
+        for ((row, y), &entry) in a.indexed_iter() {
+            a_table[y] += eq_rows[row] * entry;
+        }
+        for ((y, col), &entry) in b.indexed_iter() {
+            b_table[y] += eq_cols[col] * entry;
+        }
+
+        let polynomial = VirtualPolynomial::new(
+            vec![
+                DenseMultilinearExtension::from_evaluations_vec(inner_vars, a_table),
+                DenseMultilinearExtension::from_evaluations_vec(inner_vars, b_table),
+            ],
+            vec![(F::one(), vec![0, 1])],
+        );
+        let (sumcheck, point) = Self::vp_prove_with_randomness(&polynomial, transcript);
+        let a_evaluation = polynomial.mles[0].evaluate(&point).unwrap();
+        let b_evaluation = polynomial.mles[1].evaluate(&point).unwrap();
+        transcript.append_serializable(b"matmult_evaluations", &[a_evaluation, b_evaluation].to_vec());
+
+        MatMultProof { sumcheck, a_evaluation, b_evaluation }
+    }
+
+    // check C(r1, r2) = claimed_value and return the claims A(r1, ry) and B(ry, r2),
+    // where dimensions gives the rows of A, the inner dimension and the columns of B
+    pub fn matmult_verify(
+        proof: &MatMultProof<F>,
+        claimed_value: F,
+        r1: &[F],
+        r2: &[F],
+        dimensions: (usize, usize, usize),
+        transcript: &mut Transcript
+    ) -> Result<(SubClaim<F>, SubClaim<F>), SumcheckError> {
+        let (rows, inner_dimension, cols) = dimensions;
+        if r1.len() != dimension_vars(rows) || r2.len() != dimension_vars(cols) {
+            return Err(SumcheckError::MalformedProof("point does not index an entry of C"));
+        }
+        transcript.append_serializable(b"matmult_point", &[r1, r2].concat());
+        let subclaim = Self::verify(&proof.sumcheck, claimed_value, &vec![2; dimension_vars(inner_dimension)], transcript)?;
+        if proof.a_evaluation * proof.b_evaluation != subclaim.expected_evaluation {
+            return Err(SumcheckError::FinalEvaluationCheckFailed);
+        }
+        transcript.append_serializable(b"matmult_evaluations", &[proof.a_evaluation, proof.b_evaluation].to_vec());
+
+        let y = &subclaim.point;
+        Ok((
+            SubClaim { point: [r1, y].concat(), expected_evaluation: proof.a_evaluation },
+            SubClaim { point: [y, r2].concat(), expected_evaluation: proof.b_evaluation },
+        ))
+    }
+}
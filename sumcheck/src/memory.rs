@@ -0,0 +1,175 @@
+//! Offline memory checking (Blum et al., 1991) with the global timestamps of Spice.
+//!
+//! Every cell holds a value and the timestamp of its last access. The access at step
+//! t, counting from 1, to cell a reads the tuple (a, v, ts) and writes back
+//! (a, v', t), with v' = v for a read. The memory is consistent exactly when every
+//! read timestamp is earlier than the step reading it and the initial tuples
+//! (a, v_0, 0) together with the written tuples equal the read tuples together with
+//! the final tuples as multisets; without the ordering check an access could read a
+//! tuple written after it. Each tuple is fingerprinted as `a + γ v + γ^2 ts - τ` for
+//! challenges γ and τ, and the multisets are compared through the products of their
+//! fingerprints, each proved with the grand product argument. Here the verifier is
+//! given the tuples and settles the leaf claims itself, standing in for commitments
+//! to the trace.
+
+use ark_poly::{DenseMultilinearExtension, MultilinearExtension};
+use ark_ff::Field;
+use ark_serialize::{CanonicalSerialize, CanonicalDeserialize};
+use crate::IPForSumCheck;
+use crate::error::SumcheckError;
+use crate::grand_product::GrandProductProof;
+use crate::transcript::Transcript;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MemoryOp<F: Field> {
+    /// read the cell at the given address
+    Read(usize),
+    /// write the value to the cell at the given address
+    Write(usize, F),
+}
+
+/// what one operation reads from and writes back to its cell
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MemoryAccess<F: Field> {
+    pub address: usize,
+    pub read_value: F,
+    /// timestamp of the cell when it is read, earlier than the step of the access,
+    /// which the written tuple carries
+    pub read_timestamp: u64,
+    pub write_value: F,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct MemoryTrace<F: Field> {
+    /// initial value of every cell
+    pub init: Vec<F>,
+    pub accesses: Vec<MemoryAccess<F>>,
+    /// value and timestamp of every cell after the last access
+    pub final_values: Vec<F>,
+    pub final_timestamps: Vec<u64>,
+}
+
+#[derive(Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct MemoryCheckingProof<F: Field> {
+    /// products of the init, write, read and final fingerprints
+    pub products: [F; 4],
+    /// grand product proof of each product
+    pub product_proofs: Vec<GrandProductProof<F>>,
+}
+
+impl<F: Field> MemoryTrace<F> {
+    // run the operations on a memory holding init and record every access
+    pub fn simulate(init: Vec<F>, operations: &[MemoryOp<F>]) -> Self {
+        let mut values = init.clone();
+        let mut timestamps = vec![0; init.len()];
+        let accesses = operations.iter()
+            .zip(1..)
+            .map(|(operation, step)| {
+                let address = match *operation {
+                    MemoryOp::Read(address) | MemoryOp::Write(address, _) => address,
+                };
+                assert!(address < init.len(), "access to a missing cell");
+                let access = MemoryAccess {
+                    address,
+                    read_value: values[address],
+                    read_timestamp: timestamps[address],
+                    write_value: match *operation {
+                        MemoryOp::Read(_) => values[address],
+                        MemoryOp::Write(_, value) => value,
+                    },
+                };
+                values[address] = access.write_value;
+                timestamps[address] = step;
+                access
+            })
+            .collect();
+        MemoryTrace { init, accesses, final_values: values, final_timestamps: timestamps }
+    }
+
+    // fingerprints of the init, write, read and final multisets
+    fn fingerprints(&self, gamma: F, tau: F) -> [Vec<F>; 4] {
+        let fingerprint = |address: usize, value: F, timestamp: u64| {
+            F::from(address as u64) + gamma * value + gamma * gamma * F::from(timestamp) - tau
+        };
+        [
+            self.init.iter().enumerate().map(|(a, &v)| fingerprint(a, v, 0)).collect(),
+            self.accesses.iter().zip(1..).map(|(access, step)| fingerprint(access.address, access.write_value, step)).collect(),
+            self.accesses.iter().map(|access| fingerprint(access.address, access.read_value, access.read_timestamp)).collect(),
+            self.final_values.iter().zip(&self.final_timestamps).enumerate().map(|(a, (&v, &ts))| fingerprint(a, v, ts)).collect(),
+        ]
+    }
+}
+
+impl<F: Field> IPForSumCheck<F> {
+    pub fn memory_prove(
+        trace: &MemoryTrace<F>,
+        transcript: &mut Transcript
+    ) -> MemoryCheckingProof<F> {
+        let (gamma, tau) = fingerprint_challenges(trace, transcript);
+        let mut products = [F::zero(); 4];
+        let mut product_proofs = Vec::with_capacity(4);
+        for (product, leaves) in products.iter_mut().zip(trace.fingerprints(gamma, tau)) {
+            let (value, proof) = Self::grand_product_prove(&pad_leaves(leaves), transcript);
+            *product = value;
+            product_proofs.push(proof);
+        }
+        MemoryCheckingProof { products, product_proofs }
+    }
+
+    // check that the trace is consistent through the products of its fingerprints
+    pub fn memory_verify(
+        trace: &MemoryTrace<F>,
+        proof: &MemoryCheckingProof<F>,
+        transcript: &mut Transcript
+    ) -> Result<(), SumcheckError> {
+        if trace.final_values.len() != trace.init.len() || trace.final_timestamps.len() != trace.init.len() {
+            return Err(SumcheckError::MalformedProof("wrong number of final cells"));
+        }
+        // access i runs at step i + 1 and may only read what earlier steps wrote
+        for (i, access) in trace.accesses.iter().enumerate() {
+            if access.read_timestamp > i as u64 {
+                return Err(SumcheckError::ReadTimestampNotEarlier { access: i });
+            }
+        }
+        if proof.product_proofs.len() != 4 {
+            return Err(SumcheckError::MalformedProof("wrong number of product proofs"));
+        }
+        let (gamma, tau) = fingerprint_challenges(trace, transcript);
+        for ((&product, leaves), product_proof) in proof.products.iter()
+            .zip(trace.fingerprints(gamma, tau))
+            .zip(&proof.product_proofs)
+        {
+            let leaves = pad_leaves(leaves);
+            let num_vars = leaves.len().trailing_zeros() as usize;
+            let subclaim = Self::grand_product_verify(product, num_vars, product_proof, transcript)?;
+            let leaf_mle = DenseMultilinearExtension::from_evaluations_vec(num_vars, leaves);
+            if leaf_mle.evaluate(&subclaim.point) != Some(subclaim.expected_evaluation) {
+                return Err(SumcheckError::FinalEvaluationCheckFailed);
+            }
+        }
+
+        let [init, write, read, last] = proof.products;
+        if init * write == read * last {
+            Ok(())
+        } else {
+            Err(SumcheckError::MultisetMismatch)
+        }
+    }
+}
+
+// absorb the trace, then derive the fingerprint challenges
+fn fingerprint_challenges<F: Field>(trace: &MemoryTrace<F>, transcript: &mut Transcript) -> (F, F) {
+    transcript.append_serializable(b"memory_init", &trace.init);
+    for access in &trace.accesses {
+        transcript.append_serializable(b"memory_access", &(access.address as u64, access.read_value, access.read_timestamp, access.write_value));
+    }
+    transcript.append_serializable(b"memory_final_values", &trace.final_values);
+    transcript.append_serializable(b"memory_final_timestamps", &trace.final_timestamps);
+    (transcript.challenge_scalar(b"memory_gamma"), transcript.challenge_scalar(b"memory_tau"))
+}
+
+// pad with ones to a power of two, at least two, leaving the product unchanged
+fn pad_leaves<F: Field>(mut leaves: Vec<F>) -> Vec<F> {
+    leaves.resize(leaves.len().next_power_of_two().max(2), F::one());
+    leaves
+}
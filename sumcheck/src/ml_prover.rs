@@ -0,0 +1,73 @@
+//! Linear-time prover for multilinear polynomials given by their evaluations over the
+//! boolean hypercube (VSBW13, Thaler's Lemma 4.5). Each round reads the round polynomial
+//! off the table and then folds the table in half with the verifier challenge, so the
+//! whole protocol costs O(2^n) field operations.
+
+use ark_poly::{univariate::DensePolynomial, DenseMultilinearExtension, MultilinearExtension};
+use ark_poly::DenseUVPolynomial;
+use ark_ff::Field;
+use crate::IPForSumCheck;
+use crate::prover::ProverMsg;
+use crate::verifier::VerifierMsg;
+
+pub struct MLProverState<F: Field> {
+    /// evaluations of the polynomial with the bound variables fixed to the randomness
+    pub table: Vec<F>,
+    /// number of variables
+    pub num_vars: usize,
+    /// randomness given by the verifier at each round
+    pub randomness: Vec<F>,
+    /// current round number
+    pub round: usize,
+}
+
+impl<F: Field> IPForSumCheck<F> {
+    pub fn ml_prover_init(
+        polynomial: &DenseMultilinearExtension<F>
+    ) -> MLProverState<F> {
+        let num_vars = polynomial.num_vars();
+        if num_vars == 0 {
+            panic!("polynomial must have at least one variable");
+        }
+        MLProverState {
+            table: polynomial.evaluations.clone(),
+            num_vars,
+            randomness: Vec::with_capacity(num_vars),
+            round: 0,
+        }
+    }
+
+    pub fn ml_run_init_prover_round(
+        prover_state: &mut MLProverState<F>,
+    ) -> F {
+        prover_state.table.iter().sum()
+    }
+
+    pub fn ml_prove_round(
+        prover_state: &mut MLProverState<F>,
+        verifier_msg: &Option<VerifierMsg<F>>
+    ) -> ProverMsg<F> {
+        // fix the variable of the previous round to the challenge
+        if let Some(msg) = verifier_msg {
+            let r = msg.randomn_value;
+            let half = prover_state.table.len() / 2;
+            for i in 0..half {
+                let (even, odd) = (prover_state.table[2 * i], prover_state.table[2 * i + 1]);
+                prover_state.table[i] = even + r * (odd - even);
+            }
+            prover_state.table.truncate(half);
+            prover_state.randomness.push(r);
+        }
+
+        // the current variable is the lowest bit of the table index
+        let mut evals = [F::zero(); 2];
+        for (i, value) in prover_state.table.iter().enumerate() {
+            evals[i % 2] += value;
+        }
+        prover_state.round += 1;
+
+        ProverMsg {
+            uvpolynomial: DensePolynomial::from_coefficients_vec(vec![evals[0], evals[1] - evals[0]])
+        }
+    }
+}
@@ -0,0 +1,51 @@
+//! Oracles for the final check of the sumcheck verifier.
+//!
+//! After the last round the verifier is left with the claim g(r1,...,rv) = gv(rv).
+//! Evaluating g directly keeps the verifier as slow as the prover, so the check goes
+//! through an `EvaluationOracle`: either the polynomial itself, or a commitment to it
+//! with an evaluation proof at r, which makes the protocol a succinct argument.
+
+use ark_poly::multivariate::{SparsePolynomial, SparseTerm};
+use ark_poly::{DenseMultilinearExtension, MultilinearExtension, Polynomial};
+use ark_ec::pairing::Pairing;
+use ark_ff::Field;
+use kzg::multilinear::MultilinearKZG;
+use crate::virtual_poly::VirtualPolynomial;
+
+pub trait EvaluationOracle<F: Field> {
+    // check that the polynomial evaluates to value at point
+    fn check_evaluation(&self, point: &[F], value: F) -> bool;
+}
+
+impl<F: Field> EvaluationOracle<F> for SparsePolynomial<F, SparseTerm> {
+    fn check_evaluation(&self, point: &[F], value: F) -> bool {
+        self.evaluate(&point.to_vec()) == value
+    }
+}
+
+impl<F: Field> EvaluationOracle<F> for DenseMultilinearExtension<F> {
+    fn check_evaluation(&self, point: &[F], value: F) -> bool {
+        self.evaluate(point) == Some(value)
+    }
+}
+
+impl<F: Field> EvaluationOracle<F> for VirtualPolynomial<F> {
+    fn check_evaluation(&self, point: &[F], value: F) -> bool {
+        self.evaluate(point) == value
+    }
+}
+
+/// multilinear KZG commitment to the polynomial, opened by the prover at the point
+/// the sumcheck reduced to
+pub struct CommittedOracle<'a, E: Pairing> {
+    pub pcs: &'a MultilinearKZG<E>,
+    pub commitment: E::G1,
+    /// one quotient commitment per variable
+    pub proof: Vec<E::G1>,
+}
+
+impl<'a, E: Pairing> EvaluationOracle<E::ScalarField> for CommittedOracle<'a, E> {
+    fn check_evaluation(&self, point: &[E::ScalarField], value: E::ScalarField) -> bool {
+        self.pcs.verify(point, value, self.commitment, &self.proof)
+    }
+}
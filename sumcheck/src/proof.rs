@@ -0,0 +1,220 @@
+use ark_poly::multivariate::{SparsePolynomial, SparseTerm};
+use ark_poly::{DenseMultilinearExtension, MultilinearExtension, Polynomial};
+use ark_ff::Field;
+use ark_serialize::{CanonicalSerialize, CanonicalDeserialize};
+use crate::IPForSumCheck;
+use crate::error::SumcheckError;
+use crate::oracle::EvaluationOracle;
+use crate::prover::ProverMsg;
+use crate::verifier::{SubClaim, VerifierMsg};
+use crate::transcript::Transcript;
+use crate::utils::interpolate_uni_poly;
+
+/// non-interactive sumcheck proof obtained with the Fiat-Shamir transform
+#[derive(Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct SumcheckProof<F: Field> {
+    /// sum over the boolean hypercube, or H^m, being proved
+    pub claimed_value: F,
+    /// evaluations of each round polynomial at 0, 2, 3, ..., d; on the hypercube the
+    /// evaluation at 1 follows from the claim carried over from the previous round,
+    /// over any other domain it is sent as well
+    pub round_evaluations: Vec<Vec<F>>,
+}
+
+impl<F: Field> IPForSumCheck<F> {
+    pub fn prove(
+        polynomial: &SparsePolynomial<F, SparseTerm>,
+        transcript: &mut Transcript
+    ) -> SumcheckProof<F> {
+        let mut prover_state = Self::prover_init(polynomial);
+        let claimed_value = Self::run_init_prover_round(&mut prover_state);
+        let degrees = prover_state.degrees.clone();
+        Self::prove_rounds(&degrees, claimed_value, transcript, |verifier_msg| {
+            Self::prove_round(&mut prover_state, verifier_msg)
+        }).0
+    }
+
+    // same as prove, for the sum over H^m instead of the boolean hypercube
+    pub fn prove_with_domain(
+        polynomial: &SparsePolynomial<F, SparseTerm>,
+        domain: &[F],
+        transcript: &mut Transcript
+    ) -> SumcheckProof<F> {
+        let mut prover_state = Self::prover_init_with_domain(polynomial, domain);
+        let claimed_value = Self::run_init_prover_round(&mut prover_state);
+        let degrees = prover_state.degrees.clone();
+        Self::prove_rounds_with_domain(&degrees, claimed_value, domain, transcript, |verifier_msg| {
+            Self::prove_round(&mut prover_state, verifier_msg)
+        }).0
+    }
+
+    // same as prove, using the linear-time prover for multilinear polynomials
+    pub fn ml_prove(
+        polynomial: &DenseMultilinearExtension<F>,
+        transcript: &mut Transcript
+    ) -> SumcheckProof<F> {
+        Self::ml_prove_with_randomness(polynomial, transcript).0
+    }
+
+    // same as ml_prove, also returning the point the sumcheck reduced to
+    pub(crate) fn ml_prove_with_randomness(
+        polynomial: &DenseMultilinearExtension<F>,
+        transcript: &mut Transcript
+    ) -> (SumcheckProof<F>, Vec<F>) {
+        let mut prover_state = Self::ml_prover_init(polynomial);
+        let claimed_value = Self::ml_run_init_prover_round(&mut prover_state);
+        Self::prove_rounds(&vec![1; polynomial.num_vars()], claimed_value, transcript, |verifier_msg| {
+            Self::ml_prove_round(&mut prover_state, verifier_msg)
+        })
+    }
+
+    // run the rounds of any prover, deriving each challenge from the transcript
+    // instead of having it sampled by the verifier; also returns the challenges
+    pub(crate) fn prove_rounds(
+        degrees: &[usize],
+        claimed_value: F,
+        transcript: &mut Transcript,
+        prove_round: impl FnMut(&Option<VerifierMsg<F>>) -> ProverMsg<F>
+    ) -> (SumcheckProof<F>, Vec<F>) {
+        Self::prove_rounds_with_domain(degrees, claimed_value, &[F::zero(), F::one()], transcript, prove_round)
+    }
+
+    pub(crate) fn prove_rounds_with_domain(
+        degrees: &[usize],
+        claimed_value: F,
+        domain: &[F],
+        transcript: &mut Transcript,
+        mut prove_round: impl FnMut(&Option<VerifierMsg<F>>) -> ProverMsg<F>
+    ) -> (SumcheckProof<F>, Vec<F>) {
+        let num_vars = degrees.len();
+        transcript.append_serializable(b"num_vars", &(num_vars as u64));
+        transcript.append_serializable(b"claimed_value", &claimed_value);
+        let hypercube = is_hypercube(domain);
+        if !hypercube {
+            transcript.append_serializable(b"domain", &domain.to_vec());
+        }
+
+        let mut verifier_msg = None;
+        let mut round_evaluations = Vec::with_capacity(num_vars);
+        let mut randomness = Vec::with_capacity(num_vars);
+        for &degree in degrees {
+            let prover_msg = prove_round(&verifier_msg);
+            let evaluations: Vec<F> = (0..=degree).filter(|&x| !hypercube || x != 1)
+                .map(|x| prover_msg.uvpolynomial.evaluate(&F::from(x as u64)))
+                .collect();
+            transcript.append_serializable(b"round_evaluations", &evaluations);
+            let r = transcript.challenge_scalar(b"round_challenge");
+            randomness.push(r);
+            verifier_msg = Some(VerifierMsg { randomn_value: r });
+            round_evaluations.push(evaluations);
+        }
+
+        (SumcheckProof { claimed_value, round_evaluations }, randomness)
+    }
+
+    // check that the proof is for the claimed sum and every round of it, and return the
+    // claim about the final evaluation, where degrees bounds the round polynomial of
+    // each variable
+    pub fn verify(
+        proof: &SumcheckProof<F>,
+        claimed_value: F,
+        degrees: &[usize],
+        transcript: &mut Transcript
+    ) -> Result<SubClaim<F>, SumcheckError> {
+        Self::verify_with_domain(proof, claimed_value, degrees, &[F::zero(), F::one()], transcript)
+    }
+
+    // same as verify, for the sum over H^m instead of the boolean hypercube
+    pub fn verify_with_domain(
+        proof: &SumcheckProof<F>,
+        claimed_value: F,
+        degrees: &[usize],
+        domain: &[F],
+        transcript: &mut Transcript
+    ) -> Result<SubClaim<F>, SumcheckError> {
+        if domain.is_empty() {
+            return Err(SumcheckError::MalformedProof("empty domain"));
+        }
+        if proof.claimed_value != claimed_value {
+            return Err(SumcheckError::ClaimedValueMismatch);
+        }
+        let num_vars = degrees.len();
+        if proof.round_evaluations.len() != num_vars {
+            return Err(SumcheckError::WrongNumberOfRounds { expected: num_vars, actual: proof.round_evaluations.len() });
+        }
+        transcript.append_serializable(b"num_vars", &(num_vars as u64));
+        transcript.append_serializable(b"claimed_value", &proof.claimed_value);
+        let hypercube = is_hypercube(domain);
+        if !hypercube {
+            transcript.append_serializable(b"domain", &domain.to_vec());
+        }
+
+        let mut verifier_state = Self::verifier_init_with_degrees(degrees.to_vec(), proof.claimed_value);
+        verifier_state.domain = domain.to_vec();
+        for evaluations in &proof.round_evaluations {
+            let (&at_zero, rest) = evaluations.split_first()
+                .ok_or(SumcheckError::MalformedProof("empty round message"))?;
+            let polynomial = if hypercube {
+                // recover g(1) = claim - g(0), then the round polynomial
+                let mut points = vec![at_zero, Self::current_claim(&verifier_state) - at_zero];
+                points.extend_from_slice(rest);
+                interpolate_uni_poly(&points)
+            } else {
+                interpolate_uni_poly(evaluations)
+            };
+
+            Self::check_round(&verifier_state, &polynomial)?;
+            transcript.append_serializable(b"round_evaluations", evaluations);
+            let r = transcript.challenge_scalar(b"round_challenge");
+            Self::accept_round(&mut verifier_state, &polynomial, r);
+        }
+
+        Self::subclaim(&verifier_state)
+    }
+
+    // verify the proof and settle the final claim with the oracle
+    pub fn verify_with_oracle(
+        proof: &SumcheckProof<F>,
+        claimed_value: F,
+        degrees: &[usize],
+        transcript: &mut Transcript,
+        oracle: &impl EvaluationOracle<F>
+    ) -> Result<(), SumcheckError> {
+        let subclaim = Self::verify(proof, claimed_value, degrees, transcript)?;
+        if oracle.check_evaluation(&subclaim.point, subclaim.expected_evaluation) {
+            Ok(())
+        } else {
+            Err(SumcheckError::FinalEvaluationCheckFailed)
+        }
+    }
+}
+
+// the evaluation at 1 is left out of the round messages only for the sum over {0, 1}^m
+fn is_hypercube<F: Field>(domain: &[F]) -> bool {
+    domain == [F::zero(), F::one()]
+}
+
+#[cfg(feature = "serde")]
+mod serde_impl {
+    use ark_ff::Field;
+    use ark_serialize::{CanonicalSerialize, CanonicalDeserialize};
+    use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+    use super::SumcheckProof;
+
+    // field elements have no serde support, so the proof goes through its compressed
+    // canonical encoding
+    impl<F: Field> Serialize for SumcheckProof<F> {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            let mut bytes = Vec::new();
+            self.serialize_compressed(&mut bytes).map_err(serde::ser::Error::custom)?;
+            serializer.serialize_bytes(&bytes)
+        }
+    }
+
+    impl<'de, F: Field> Deserialize<'de> for SumcheckProof<F> {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            let bytes = Vec::<u8>::deserialize(deserializer)?;
+            SumcheckProof::deserialize_compressed(bytes.as_slice()).map_err(de::Error::custom)
+        }
+    }
+}
@@ -1,20 +1,29 @@
 use ark_poly::{univariate::DensePolynomial, multivariate::{SparsePolynomial, SparseTerm, Term}};
 use ark_poly::{Polynomial, DenseUVPolynomial, DenseMVPolynomial};
 use ark_std::cfg_into_iter;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
 use ark_ff::Field;
+use ark_serialize::{CanonicalSerialize, CanonicalDeserialize};
 use crate::IPForSumCheck;
-use crate::verifier::VerifierMsg;
+use crate::verifier::{variable_degrees, VerifierMsg};
 
+#[derive(Clone, CanonicalSerialize, CanonicalDeserialize)]
 pub struct ProverState<F: Field> {
     /// polynomial to be proved
     pub polynomial: SparsePolynomial<F, SparseTerm>,
     /// number of variables
     pub num_vars: usize,
+    /// largest power of each variable, the degree of its round polynomial
+    pub degrees: Vec<usize>,
+    /// set H the sum runs over in every variable, {0, 1} for the hypercube
+    pub domain: Vec<F>,
     /// randomness given by the verifier at each round
     pub randomness: Vec<F>,
     /// current round number
     pub round: usize,
 }
+#[derive(Clone, CanonicalSerialize, CanonicalDeserialize)]
 pub struct ProverMsg<F: Field> {
     pub uvpolynomial: DensePolynomial<F>,
 }
@@ -23,13 +32,24 @@ impl<F: Field> IPForSumCheck<F> {
     pub fn prover_init(
         polynomial:&SparsePolynomial<F, SparseTerm>
     ) -> ProverState<F> {
+        Self::prover_init_with_domain(polynomial, &[F::zero(), F::one()])
+    }
+
+    // prover for the sum over H^m instead of the boolean hypercube
+    pub fn prover_init_with_domain(
+        polynomial:&SparsePolynomial<F, SparseTerm>,
+        domain: &[F]
+    ) -> ProverState<F> {
+        assert!(!domain.is_empty(), "domain must not be empty");
         let num_vars = polynomial.num_vars();
         if num_vars == 0 {
             panic!("polynomial must have at least one variable");
         }
         ProverState {
             polynomial: polynomial.clone(),
-            num_vars: num_vars,
+            num_vars,
+            degrees: variable_degrees(polynomial),
+            domain: domain.to_vec(),
             randomness: Vec::with_capacity(polynomial.num_vars()),
             round: 0,
         }
@@ -40,20 +60,13 @@ impl<F: Field> IPForSumCheck<F> {
     ) -> F {
         // Evaluate the polynomial at all possible points
         let num_vars = prover_state.polynomial.num_vars();
-    
-        let mut accum = F::zero();
-        for i in 0..2i32.pow(num_vars as u32) {
-            let mut counter = i;
-            let mut coeffs = Vec::with_capacity(num_vars);
-            for _ in 0..num_vars {
-                coeffs.push(if counter % 2 == 0 { F::zero()} else { F::one()});
-                counter /= 2;
-            }
-            accum += prover_state.polynomial.evaluate(&coeffs);
+        let size = prover_state.domain.len();
+        let (polynomial, domain) = (&prover_state.polynomial, &prover_state.domain);
 
-        }
-
-        accum
+        let accum = domain_sum(size.pow(num_vars as u32), 1, |i, accum| {
+            accum[0] += polynomial.evaluate(&domain_point(i, num_vars, domain));
+        });
+        accum[0]
     }
 
     pub fn prove_round(
@@ -65,32 +78,30 @@ impl<F: Field> IPForSumCheck<F> {
         } 
         
         let to_sum = prover_state.num_vars - prover_state.round - 1;
+        let size = prover_state.domain.len();
+        let round = prover_state.round;
+        let (polynomial, randomness, domain) = (&prover_state.polynomial, &prover_state.randomness, &prover_state.domain);
 
-        let mut coeffs = vec![F::zero(); prover_state.num_vars];
-        for i in 0..2i32.pow(to_sum as u32) {
+        let coeffs = domain_sum(size.pow(to_sum as u32), prover_state.degrees[round] + 1, |i, coeffs| {
             let mut inputs = Vec::with_capacity(prover_state.num_vars);
-            inputs.extend(prover_state.randomness.clone());
+            inputs.extend(randomness.iter().cloned());
             inputs.push(F::zero());
-            let mut counter = i;
-            for _ in 0..to_sum{
-                inputs.push(if counter % 2 == 0 { F::zero()} else { F::one()});
-                counter /= 2;
-            }
+            inputs.extend(domain_point(i, to_sum, domain));
 
             // conribute to polynomial
-            for (coeff, term) in cfg_into_iter!(&prover_state.polynomial.terms) {
+            for (coeff, term) in &polynomial.terms {
                 let mut coeff_accum: F = F::one();
                 let mut which = 0;
                 for (&var, pow) in term.vars().iter().zip(term.powers()) {
-                    if var == prover_state.round {
+                    if var == round {
                         which = pow;
                     } else {
-                        coeff_accum *= inputs[var].pow(&[pow as u64]);
+                        coeff_accum *= inputs[var].pow([pow as u64]);
                     }
                 }
                 coeffs[which] += coeff.mul(&coeff_accum);
             }
-        }
+        });
         prover_state.round += 1;
         
         ProverMsg {
@@ -100,3 +111,41 @@ impl<F: Field> IPForSumCheck<F> {
 
 }
 
+// number of points summed by one task of the parallel prover
+const CHUNK_SIZE: usize = 1 << 8;
+
+// i-th point of H^num_vars, the first coordinate varying fastest
+fn domain_point<F: Field>(i: usize, num_vars: usize, domain: &[F]) -> Vec<F> {
+    let mut counter = i;
+    let mut point = Vec::with_capacity(num_vars);
+    for _ in 0..num_vars {
+        point.push(domain[counter % domain.len()]);
+        counter /= domain.len();
+    }
+    point
+}
+
+// accumulate `len` sums over the points 0..count. The points are split into fixed
+// chunks whose partial sums are added in chunk order, so the result does not depend
+// on the number of threads with the parallel feature
+fn domain_sum<F: Field>(
+    count: usize,
+    len: usize,
+    accumulate: impl Fn(usize, &mut [F]) + Send + Sync
+) -> Vec<F> {
+    let partials: Vec<Vec<F>> = cfg_into_iter!(0..count.div_ceil(CHUNK_SIZE))
+        .map(|chunk| {
+            let mut partial = vec![F::zero(); len];
+            for i in chunk * CHUNK_SIZE..count.min((chunk + 1) * CHUNK_SIZE) {
+                accumulate(i, &mut partial);
+            }
+            partial
+        })
+        .collect();
+    partials.into_iter().fold(vec![F::zero(); len], |mut sums, partial| {
+        for (sum, value) in sums.iter_mut().zip(partial) {
+            *sum += value;
+        }
+        sums
+    })
+}
@@ -0,0 +1,168 @@
+//! Counting satisfying assignments of CNF formulas with sumcheck (#SAT, Thaler 4.2).
+//!
+//! Each clause `l1 v ... v lk` is arithmetized as `1 - prod_j (1 - l_j)`, with `x` for
+//! a positive literal and `1 - x` for a negated one. The formula is the product of its
+//! clauses, which is 1 exactly on satisfying assignments, so its sum over the boolean
+//! hypercube is the number of satisfying assignments. The prover works on the clause
+//! tables as a virtual polynomial; the verifier evaluates the formula itself at the
+//! final point in time linear in its size.
+
+use std::path::Path;
+use ark_poly::DenseMultilinearExtension;
+use ark_ff::{Field, PrimeField};
+use crate::IPForSumCheck;
+use crate::error::SumcheckError;
+use crate::proof::SumcheckProof;
+use crate::transcript::Transcript;
+use crate::utils::field_to_u64;
+use crate::virtual_poly::VirtualPolynomial;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct CnfFormula {
+    pub num_vars: usize,
+    /// clauses as DIMACS literals: variable i is i for a positive literal and -i for
+    /// a negated one, variables being numbered from 1
+    pub clauses: Vec<Vec<i64>>,
+}
+
+impl CnfFormula {
+    // normalize the clauses: repeated literals are merged and clauses holding both a
+    // variable and its negation are dropped, so that every clause is multilinear
+    pub fn new(num_vars: usize, clauses: Vec<Vec<i64>>) -> Result<Self, &'static str> {
+        // the sumcheck needs at least one round
+        if num_vars == 0 {
+            return Err("Formula must have at least one variable");
+        }
+        let mut normalized = vec![];
+        for mut clause in clauses {
+            if clause.iter().any(|&literal| literal == 0 || literal.unsigned_abs() as usize > num_vars) {
+                return Err("Literal refers to a missing variable");
+            }
+            clause.sort_unstable();
+            clause.dedup();
+            if !clause.iter().any(|literal| clause.contains(&-literal)) {
+                normalized.push(clause);
+            }
+        }
+        Ok(CnfFormula { num_vars, clauses: normalized })
+    }
+
+    pub fn parse_dimacs(input: &str) -> Result<Self, &'static str> {
+        let mut header = None;
+        let mut clauses = vec![];
+        let mut clause = vec![];
+        for line in input.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('c') {
+                continue;
+            }
+            // some benchmark files end with a '%' line
+            if line.starts_with('%') {
+                break;
+            }
+            if line.starts_with('p') {
+                let fields: Vec<&str> = line.split_whitespace().collect();
+                if header.is_some() || fields.len() != 4 || fields[1] != "cnf" {
+                    return Err("Invalid problem line");
+                }
+                let num_vars = fields[2].parse::<usize>().map_err(|_| "Invalid variable count")?;
+                let num_clauses = fields[3].parse::<usize>().map_err(|_| "Invalid clause count")?;
+                header = Some((num_vars, num_clauses));
+                continue;
+            }
+            if header.is_none() {
+                return Err("Clause before the problem line");
+            }
+            for token in line.split_whitespace() {
+                match token.parse::<i64>().map_err(|_| "Invalid literal")? {
+                    0 => clauses.push(std::mem::take(&mut clause)),
+                    literal => clause.push(literal),
+                }
+            }
+        }
+        if !clause.is_empty() {
+            clauses.push(clause);
+        }
+
+        let (num_vars, num_clauses) = header.ok_or("Missing problem line")?;
+        if clauses.len() != num_clauses {
+            return Err("Clause count does not match the problem line");
+        }
+        Self::new(num_vars, clauses)
+    }
+
+    pub fn from_dimacs_file(path: impl AsRef<Path>) -> Result<Self, &'static str> {
+        let input = std::fs::read_to_string(path).map_err(|_| "Could not read the DIMACS file")?;
+        Self::parse_dimacs(&input)
+    }
+
+    // degree of the round polynomials, one factor per clause
+    pub fn degree(&self) -> usize {
+        self.clauses.len().max(1)
+    }
+
+    // arithmetized formula at any point, variable i + 1 being coordinate i
+    pub fn evaluate<F: Field>(&self, point: &[F]) -> F {
+        self.clauses.iter()
+            .map(|clause| {
+                let unsatisfied = clause.iter().fold(F::one(), |acc, &literal| {
+                    acc * (F::one() - literal_value(literal, point))
+                });
+                F::one() - unsatisfied
+            })
+            .product()
+    }
+
+    // clause tables over the hypercube, multiplied in a single term
+    pub fn to_virtual_polynomial<F: Field>(&self) -> VirtualPolynomial<F> {
+        let size = 1usize << self.num_vars;
+        let mut mles: Vec<DenseMultilinearExtension<F>> = self.clauses.iter()
+            .map(|clause| {
+                let evaluations = (0..size)
+                    .map(|assignment| {
+                        let satisfied = clause.iter().any(|&literal| {
+                            let value = (assignment >> (literal.unsigned_abs() - 1)) & 1 == 1;
+                            value == (literal > 0)
+                        });
+                        if satisfied { F::one() } else { F::zero() }
+                    })
+                    .collect();
+                DenseMultilinearExtension::from_evaluations_vec(self.num_vars, evaluations)
+            })
+            .collect();
+        if mles.is_empty() {
+            mles.push(DenseMultilinearExtension::from_evaluations_vec(self.num_vars, vec![F::one(); size]));
+        }
+        let indices = (0..mles.len()).collect();
+        VirtualPolynomial::new(mles, vec![(F::one(), indices)])
+    }
+}
+
+impl<F: PrimeField> IPForSumCheck<F> {
+    // prove the number of satisfying assignments, given as the proof's claimed value
+    pub fn sat_prove(
+        formula: &CnfFormula,
+        transcript: &mut Transcript
+    ) -> SumcheckProof<F> {
+        Self::vp_prove(&formula.to_virtual_polynomial(), transcript)
+    }
+
+    // check the proof and return the number of satisfying assignments
+    pub fn sat_verify(
+        formula: &CnfFormula,
+        proof: &SumcheckProof<F>,
+        transcript: &mut Transcript
+    ) -> Result<u64, SumcheckError> {
+        // the proof carries the count, which is returned once the proof checks out
+        let subclaim = Self::verify(proof, proof.claimed_value, &vec![formula.degree(); formula.num_vars], transcript)?;
+        if formula.evaluate(&subclaim.point) != subclaim.expected_evaluation {
+            return Err(SumcheckError::FinalEvaluationCheckFailed);
+        }
+        field_to_u64(proof.claimed_value)
+            .ok_or(SumcheckError::MalformedProof("count does not fit into 64 bits"))
+    }
+}
+
+fn literal_value<F: Field>(literal: i64, point: &[F]) -> F {
+    let value = point[literal.unsigned_abs() as usize - 1];
+    if literal > 0 { value } else { F::one() - value }
+}
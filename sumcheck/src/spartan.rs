@@ -0,0 +1,256 @@
+//! Spartan-style proof of R1CS satisfiability (Setty, 2020), without the commitment
+//! to the witness.
+//!
+//! An instance holds sparse matrices A, B, C and is satisfied by z = (1, x, w) if
+//! `Az ∘ Bz = Cz`. The outer sumcheck is a zerocheck of `Az(x) Bz(x) - Cz(x)`, that is
+//! a sumcheck of `eq(τ, x) (Az(x) Bz(x) - Cz(x))` with target 0, which leaves claims
+//! about Az, Bz and Cz at a point rx. These are combined with random coefficients into
+//! `Σ_y (ra A(rx, y) + rb B(rx, y) + rc C(rx, y)) Z(y)`, and the inner sumcheck reduces
+//! it to the evaluation of Z at a point ry. The verifier evaluates the matrices itself
+//! in time linear in their number of entries, and Z splits into the public part and
+//! the witness MLE, so that only one witness evaluation is left to the caller.
+
+use ark_poly::{DenseMultilinearExtension, MultilinearExtension};
+use ark_ff::Field;
+use ark_serialize::{CanonicalSerialize, CanonicalDeserialize};
+use crate::IPForSumCheck;
+use crate::error::SumcheckError;
+use crate::proof::SumcheckProof;
+use crate::transcript::Transcript;
+use crate::utils::eq_table;
+use crate::verifier::SubClaim;
+use crate::virtual_poly::{VirtualPolynomial, VPAuxInfo};
+
+/// rank-1 constraint system over z = (1, inputs, witness), given by the entries
+/// (row, column, value) of its matrices
+#[derive(Clone, Debug)]
+pub struct R1CS<F: Field> {
+    pub num_constraints: usize,
+    pub num_inputs: usize,
+    pub num_witness: usize,
+    pub a: Vec<(usize, usize, F)>,
+    pub b: Vec<(usize, usize, F)>,
+    pub c: Vec<(usize, usize, F)>,
+}
+
+#[derive(Clone, Debug, PartialEq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct SpartanProof<F: Field> {
+    /// zerocheck of Az Bz - Cz over the constraints
+    pub outer: SumcheckProof<F>,
+    /// evaluations of Az, Bz and Cz at the point of the outer sumcheck
+    pub matrix_evaluations: [F; 3],
+    /// sumcheck of the combined matrices times Z
+    pub inner: SumcheckProof<F>,
+    /// evaluation of the witness MLE at the point of the inner sumcheck
+    pub witness_evaluation: F,
+}
+
+impl<F: Field> R1CS<F> {
+    pub fn new(
+        num_constraints: usize,
+        num_inputs: usize,
+        num_witness: usize,
+        a: Vec<(usize, usize, F)>,
+        b: Vec<(usize, usize, F)>,
+        c: Vec<(usize, usize, F)>
+    ) -> Result<Self, &'static str> {
+        let r1cs = R1CS { num_constraints, num_inputs, num_witness, a, b, c };
+        r1cs.validate()?;
+        Ok(r1cs)
+    }
+
+    // every entry must lie within the constraints and the columns of z; the verifier
+    // checks this again since the fields are public
+    pub fn validate(&self) -> Result<(), &'static str> {
+        if self.num_constraints == 0 {
+            return Err("Instance must have at least one constraint");
+        }
+        let width = 1 + self.num_inputs + self.num_witness;
+        for &(row, column, _) in self.a.iter().chain(&self.b).chain(&self.c) {
+            if row >= self.num_constraints || column >= width {
+                return Err("Matrix entry out of range");
+            }
+        }
+        Ok(())
+    }
+
+    pub fn is_satisfied(&self, inputs: &[F], witness: &[F]) -> bool {
+        let z = self.z(inputs, witness);
+        let (az, bz, cz) = (self.multiply(&self.a, &z), self.multiply(&self.b, &z), self.multiply(&self.c, &z));
+        (0..self.num_constraints).all(|i| az[i] * bz[i] == cz[i])
+    }
+
+    // variables indexing the constraints, at least one
+    pub fn row_vars(&self) -> usize {
+        (self.num_constraints.next_power_of_two().trailing_zeros() as usize).max(1)
+    }
+
+    // variables of the witness MLE; Z has one more variable, its highest one selecting
+    // the public part (1, inputs)
+    pub fn witness_vars(&self) -> usize {
+        self.num_witness.max(1 + self.num_inputs).next_power_of_two().trailing_zeros() as usize
+    }
+
+    // z laid out as the padded witness followed by the padded (1, inputs)
+    fn z(&self, inputs: &[F], witness: &[F]) -> Vec<F> {
+        assert_eq!(inputs.len(), self.num_inputs, "wrong number of inputs");
+        assert_eq!(witness.len(), self.num_witness, "wrong number of witness values");
+        let half = 1 << self.witness_vars();
+        let mut z = vec![F::zero(); 2 * half];
+        z[..witness.len()].copy_from_slice(witness);
+        z[half] = F::one();
+        z[half + 1..half + 1 + inputs.len()].copy_from_slice(inputs);
+        z
+    }
+
+    // position in the padded z of a column of the matrices
+    fn column(&self, column: usize) -> usize {
+        if column <= self.num_inputs {
+            (1 << self.witness_vars()) + column
+        } else {
+            column - self.num_inputs - 1
+        }
+    }
+
+    // bind the transcript to the instance before any challenge is drawn
+    fn absorb(&self, transcript: &mut Transcript) {
+        let sizes = [self.num_constraints, self.num_inputs, self.num_witness].map(|size| size as u64);
+        transcript.append_serializable(b"spartan_sizes", &sizes.to_vec());
+        for (label, matrix) in [(b"spartan_a", &self.a), (b"spartan_b", &self.b), (b"spartan_c", &self.c)] {
+            let entries: Vec<(u64, u64, F)> = matrix.iter()
+                .map(|&(row, column, value)| (row as u64, column as u64, value))
+                .collect();
+            transcript.append_serializable(label, &entries);
+        }
+    }
+
+    // matrix-vector product over the padded rows
+    fn multiply(&self, matrix: &[(usize, usize, F)], z: &[F]) -> Vec<F> {
+        let mut product = vec![F::zero(); 1 << self.row_vars()];
+        for &(row, column, value) in matrix {
+            product[row] += value * z[self.column(column)];
+        }
+        product
+    }
+
+    // table over y of the rows of the matrices weighted by eq(rx, .) and the coefficients
+    fn combined_columns(&self, rx: &[F], coefficients: &[F]) -> Vec<F> {
+        let eq = eq_table(rx);
+        let mut table = vec![F::zero(); 1 << (self.witness_vars() + 1)];
+        for (matrix, &coefficient) in [&self.a, &self.b, &self.c].into_iter().zip(coefficients) {
+            for &(row, column, value) in matrix {
+                table[self.column(column)] += coefficient * eq[row] * value;
+            }
+        }
+        table
+    }
+}
+
+impl<F: Field> IPForSumCheck<F> {
+    // the instance and inputs are absorbed here; a commitment to the witness must be
+    // absorbed by the caller before calling, so that τ depends on it
+    pub fn spartan_prove(
+        r1cs: &R1CS<F>,
+        inputs: &[F],
+        witness: &[F],
+        transcript: &mut Transcript
+    ) -> SpartanProof<F> {
+        let z = r1cs.z(inputs, witness);
+        r1cs.absorb(transcript);
+        transcript.append_serializable(b"spartan_inputs", &inputs.to_vec());
+
+        // outer sumcheck over the constraints
+        let row_vars = r1cs.row_vars();
+        let products = [&r1cs.a, &r1cs.b, &r1cs.c]
+            .map(|matrix| DenseMultilinearExtension::from_evaluations_vec(row_vars, r1cs.multiply(matrix, &z)));
+        let polynomial = VirtualPolynomial::new(products.to_vec(), vec![
+            (F::one(), vec![0, 1]),
+            (-F::one(), vec![2]),
+        ]);
+        let (outer, rx) = Self::zerocheck_prove_with_randomness(&polynomial, transcript);
+        let matrix_evaluations = products.map(|mle| mle.evaluate(&rx).unwrap());
+        let coefficients = combination(&matrix_evaluations, transcript);
+
+        // inner sumcheck over the columns
+        let column_vars = r1cs.witness_vars() + 1;
+        let polynomial = VirtualPolynomial::new(
+            vec![
+                DenseMultilinearExtension::from_evaluations_vec(column_vars, r1cs.combined_columns(&rx, &coefficients)),
+                DenseMultilinearExtension::from_evaluations_vec(column_vars, z.clone()),
+            ],
+            vec![(F::one(), vec![0, 1])],
+        );
+        let (inner, ry) = Self::vp_prove_with_randomness(&polynomial, transcript);
+        let half = z.len() / 2;
+        let witness_evaluation = DenseMultilinearExtension::from_evaluations_slice(column_vars - 1, &z[..half])
+            .evaluate(&ry[..column_vars - 1])
+            .unwrap();
+        transcript.append_serializable(b"spartan_witness_evaluation", &witness_evaluation);
+
+        SpartanProof { outer, matrix_evaluations, inner, witness_evaluation }
+    }
+
+    // check the proof for the public inputs and return the claim about the witness MLE,
+    // to be settled against a commitment to the witness, absorbed beforehand as for
+    // spartan_prove
+    pub fn spartan_verify(
+        r1cs: &R1CS<F>,
+        inputs: &[F],
+        proof: &SpartanProof<F>,
+        transcript: &mut Transcript
+    ) -> Result<SubClaim<F>, SumcheckError> {
+        if r1cs.validate().is_err() {
+            return Err(SumcheckError::MalformedProof("invalid instance"));
+        }
+        if inputs.len() != r1cs.num_inputs {
+            return Err(SumcheckError::MalformedProof("wrong number of inputs"));
+        }
+        r1cs.absorb(transcript);
+        transcript.append_serializable(b"spartan_inputs", &inputs.to_vec());
+
+        let aux_info = VPAuxInfo { num_vars: r1cs.row_vars(), max_degree: 2 };
+        let subclaim = Self::zerocheck_verify(&proof.outer, &aux_info, transcript)?;
+        let [az, bz, cz] = proof.matrix_evaluations;
+        if az * bz - cz != subclaim.expected_evaluation {
+            return Err(SumcheckError::FinalEvaluationCheckFailed);
+        }
+        let rx = subclaim.point;
+        let coefficients = combination(&proof.matrix_evaluations, transcript);
+
+        let claimed_value: F = coefficients.iter().zip(&proof.matrix_evaluations).map(|(&r, &v)| r * v).sum();
+        let column_vars = r1cs.witness_vars() + 1;
+        let subclaim = Self::verify(&proof.inner, claimed_value, &vec![2; column_vars], transcript)?;
+        let ry = subclaim.point;
+
+        // Z(ry) from the witness evaluation and the public part (1, inputs)
+        let (&last, point) = ry.split_last().unwrap();
+        let mut public = vec![F::one()];
+        public.extend_from_slice(inputs);
+        public.resize(1 << point.len(), F::zero());
+        let public_evaluation = DenseMultilinearExtension::from_evaluations_vec(point.len(), public)
+            .evaluate(point)
+            .unwrap();
+        let z_evaluation = (F::one() - last) * proof.witness_evaluation + last * public_evaluation;
+
+        let matrix_evaluation: F = r1cs.combined_columns(&rx, &coefficients)
+            .into_iter()
+            .zip(eq_table(&ry))
+            .map(|(value, eq)| value * eq)
+            .sum();
+        if matrix_evaluation * z_evaluation != subclaim.expected_evaluation {
+            return Err(SumcheckError::FinalEvaluationCheckFailed);
+        }
+        transcript.append_serializable(b"spartan_witness_evaluation", &proof.witness_evaluation);
+
+        Ok(SubClaim {
+            point: point.to_vec(),
+            expected_evaluation: proof.witness_evaluation,
+        })
+    }
+}
+
+// absorb the evaluations of Az, Bz and Cz, then derive the coefficients combining them
+fn combination<F: Field>(matrix_evaluations: &[F; 3], transcript: &mut Transcript) -> Vec<F> {
+    transcript.append_serializable(b"spartan_matrix_evaluations", &matrix_evaluations.to_vec());
+    (0..3).map(|_| transcript.challenge_scalar(b"spartan_combination")).collect()
+}
@@ -0,0 +1,144 @@
+//! Space-efficient prover for multilinear polynomials whose evaluation table does not
+//! fit in memory (Cormode, Mitzenmacher and Thaler, 2012).
+//!
+//! Instead of folding a table, every round streams the evaluations again in index
+//! order. The round polynomial of variable j weighs each evaluation by eq(r, b) for
+//! the bits b below j, which is updated incrementally from one index to the next, so
+//! a round costs O(2^n) field operations and the prover keeps O(n) field elements.
+//! The round polynomials, and hence the transcripts, are the same as the in-memory
+//! multilinear prover's.
+
+use ark_poly::{univariate::DensePolynomial, DenseUVPolynomial};
+use ark_ff::Field;
+use crate::IPForSumCheck;
+use crate::proof::SumcheckProof;
+use crate::prover::ProverMsg;
+use crate::transcript::Transcript;
+use crate::utils::eq_eval_at_index;
+use crate::verifier::VerifierMsg;
+
+pub struct StreamingProverState<F: Field, S> {
+    /// yields the evaluations over the hypercube in index order on every call
+    pub stream: S,
+    /// number of variables
+    pub num_vars: usize,
+    /// randomness given by the verifier at each round
+    pub randomness: Vec<F>,
+    /// current round number
+    pub round: usize,
+}
+
+impl<F: Field> IPForSumCheck<F> {
+    pub fn streaming_prover_init<S, I>(
+        stream: S,
+        num_vars: usize
+    ) -> StreamingProverState<F, S>
+    where
+        S: Fn() -> I,
+        I: Iterator<Item = F>,
+    {
+        if num_vars == 0 {
+            panic!("polynomial must have at least one variable");
+        }
+        StreamingProverState {
+            stream,
+            num_vars,
+            randomness: Vec::with_capacity(num_vars),
+            round: 0,
+        }
+    }
+
+    pub fn streaming_run_init_prover_round<S, I>(
+        prover_state: &mut StreamingProverState<F, S>,
+    ) -> F
+    where
+        S: Fn() -> I,
+        I: Iterator<Item = F>,
+    {
+        (prover_state.stream)().take(1 << prover_state.num_vars).sum()
+    }
+
+    pub fn streaming_prove_round<S, I>(
+        prover_state: &mut StreamingProverState<F, S>,
+        verifier_msg: &Option<VerifierMsg<F>>
+    ) -> ProverMsg<F>
+    where
+        S: Fn() -> I,
+        I: Iterator<Item = F>,
+    {
+        if let Some(msg) = verifier_msg {
+            prover_state.randomness.push(msg.randomn_value);
+        }
+
+        // g(t) = sum over the index of eq(r, low bits) f(index) for bit j equal to t
+        let j = prover_state.round;
+        let mut weights = EqWeights::new(&prover_state.randomness);
+        let mut evals = [F::zero(); 2];
+        for (index, value) in (prover_state.stream)().take(1 << prover_state.num_vars).enumerate() {
+            evals[(index >> j) & 1] += weights.current * value;
+            weights.advance();
+        }
+        prover_state.round += 1;
+
+        ProverMsg {
+            uvpolynomial: DensePolynomial::from_coefficients_vec(vec![evals[0], evals[1] - evals[0]])
+        }
+    }
+
+    // same proof as ml_prove, streaming the evaluations once per round
+    pub fn streaming_prove<S, I>(
+        stream: S,
+        num_vars: usize,
+        transcript: &mut Transcript
+    ) -> SumcheckProof<F>
+    where
+        S: Fn() -> I,
+        I: Iterator<Item = F>,
+    {
+        let mut prover_state = Self::streaming_prover_init(stream, num_vars);
+        let claimed_value = Self::streaming_run_init_prover_round(&mut prover_state);
+        Self::prove_rounds(&vec![1; num_vars], claimed_value, transcript, |verifier_msg| {
+            Self::streaming_prove_round(&mut prover_state, verifier_msg)
+        }).0
+    }
+}
+
+// eq(point, b) for b = 0, 1, 2, ... wrapping around after 2^len, updated with the
+// ratios r / (1 - r) and (1 - r) / r of the bits flipped by each increment
+struct EqWeights<'a, F: Field> {
+    point: &'a [F],
+    /// ratios (r / (1 - r), (1 - r) / r) per coordinate, None if some r is 0 or 1
+    ratios: Option<Vec<(F, F)>>,
+    index: usize,
+    current: F,
+}
+
+impl<'a, F: Field> EqWeights<'a, F> {
+    fn new(point: &'a [F]) -> Self {
+        let ratios = point.iter()
+            .map(|&r| Some((r * (F::one() - r).inverse()?, (F::one() - r) * r.inverse()?)))
+            .collect();
+        EqWeights {
+            point,
+            ratios,
+            index: 0,
+            current: eq_eval_at_index(0, point),
+        }
+    }
+
+    fn advance(&mut self) {
+        let len = self.point.len();
+        self.index = (self.index + 1) % (1 << len);
+        match &self.ratios {
+            Some(ratios) if self.index != 0 => {
+                // trailing ones of the previous index become zeros, the next bit a one
+                let flipped = self.index.trailing_zeros() as usize;
+                for &(_, down) in &ratios[..flipped] {
+                    self.current *= down;
+                }
+                self.current *= ratios[flipped].0;
+            }
+            _ => self.current = eq_eval_at_index(self.index, self.point),
+        }
+    }
+}
@@ -0,0 +1,63 @@
+//! Hash-based transcript for the Fiat-Shamir transform.
+//!
+//! Every message is absorbed into a running SHA-256 state, and challenges are derived
+//! from that state, so the verifier messages of the interactive protocol can be
+//! recomputed by anyone who replays the same messages.
+
+use ark_ff::Field;
+use ark_serialize::{CanonicalSerialize, Compress};
+use sha2::{Digest, Sha256};
+
+#[derive(Clone)]
+pub struct Transcript {
+    /// digest of everything absorbed so far
+    state: [u8; 32],
+}
+
+impl Transcript {
+    pub fn new(label: &'static [u8]) -> Self {
+        let mut transcript = Transcript { state: [0u8; 32] };
+        transcript.append_message(b"domain_separator", label);
+        transcript
+    }
+
+    pub fn append_message(&mut self, label: &'static [u8], message: &[u8]) {
+        let mut hasher = Sha256::new();
+        hasher.update(self.state);
+        hasher.update((label.len() as u64).to_le_bytes());
+        hasher.update(label);
+        hasher.update((message.len() as u64).to_le_bytes());
+        hasher.update(message);
+        self.state = hasher.finalize().into();
+    }
+
+    pub fn append_serializable<T: CanonicalSerialize>(&mut self, label: &'static [u8], message: &T) {
+        let mut bytes = Vec::new();
+        message.serialize_compressed(&mut bytes).expect("serialization into a vector cannot fail");
+        self.append_message(label, &bytes);
+    }
+
+    // derive a field element from the current state, then absorb it
+    pub fn challenge_scalar<F: Field>(&mut self, label: &'static [u8]) -> F {
+        self.append_message(label, &[]);
+        let size = F::zero().serialized_size(Compress::No);
+
+        // rejection sampling over blocks of hash output
+        let mut counter = 0u64;
+        let challenge = loop {
+            let mut bytes = Vec::with_capacity(size + 32);
+            while bytes.len() < size {
+                let mut hasher = Sha256::new();
+                hasher.update(self.state);
+                hasher.update(counter.to_le_bytes());
+                bytes.extend_from_slice(&hasher.finalize());
+                counter += 1;
+            }
+            if let Some(challenge) = F::from_random_bytes(&bytes[..size]) {
+                break challenge;
+            }
+        };
+        self.append_serializable(label, &challenge);
+        challenge
+    }
+}
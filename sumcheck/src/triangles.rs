@@ -0,0 +1,139 @@
+//! Counting triangles in a graph with sumcheck (Thaler 4.3).
+//!
+//! With the adjacency matrix padded to 2^k vertices and A its multilinear extension,
+//! the sum of `A(x, y) A(y, z) A(x, z)` over x, y, z in {0, 1}^k counts every triangle
+//! once per ordering of its vertices, that is six times. The prover runs the sumcheck
+//! on the three adjacency tables; the verifier evaluates A at the three pairs of the
+//! final point from the matrix itself.
+
+use std::path::Path;
+use ark_poly::{DenseMultilinearExtension, MultilinearExtension};
+use ark_ff::{Field, PrimeField};
+use crate::IPForSumCheck;
+use crate::error::SumcheckError;
+use crate::proof::SumcheckProof;
+use crate::transcript::Transcript;
+use crate::utils::field_to_u64;
+use crate::virtual_poly::VirtualPolynomial;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Graph {
+    pub num_vertices: usize,
+    /// symmetric adjacency matrix in row-major order
+    pub adjacency: Vec<bool>,
+}
+
+impl Graph {
+    // undirected graph without self-loops
+    pub fn from_edges(num_vertices: usize, edges: &[(usize, usize)]) -> Result<Self, &'static str> {
+        let mut adjacency = vec![false; num_vertices * num_vertices];
+        for &(u, v) in edges {
+            if u >= num_vertices || v >= num_vertices {
+                return Err("Edge refers to a missing vertex");
+            }
+            if u != v {
+                adjacency[u * num_vertices + v] = true;
+                adjacency[v * num_vertices + u] = true;
+            }
+        }
+        Ok(Graph { num_vertices, adjacency })
+    }
+
+    // one edge "u v" per line with vertices numbered from 0; lines starting with '#'
+    // are comments
+    pub fn parse_edge_list(input: &str) -> Result<Self, &'static str> {
+        let mut edges = vec![];
+        for line in input.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let vertices: Vec<usize> = line.split_whitespace()
+                .map(|token| token.parse().map_err(|_| "Invalid vertex"))
+                .collect::<Result<_, _>>()?;
+            if vertices.len() != 2 {
+                return Err("Edge must have two vertices");
+            }
+            edges.push((vertices[0], vertices[1]));
+        }
+        let num_vertices = edges.iter().map(|&(u, v)| u.max(v) + 1).max().unwrap_or(0);
+        Self::from_edges(num_vertices, &edges)
+    }
+
+    pub fn from_edge_list_file(path: impl AsRef<Path>) -> Result<Self, &'static str> {
+        let input = std::fs::read_to_string(path).map_err(|_| "Could not read the edge list file")?;
+        Self::parse_edge_list(&input)
+    }
+
+    // number of variables indexing a vertex
+    pub fn vertex_vars(&self) -> usize {
+        (self.num_vertices.next_power_of_two().trailing_zeros() as usize).max(1)
+    }
+
+    // extension of the padded adjacency matrix, x in the low bits and y in the high bits
+    pub fn adjacency_mle<F: Field>(&self) -> DenseMultilinearExtension<F> {
+        let k = self.vertex_vars();
+        let mut evaluations = vec![F::zero(); 1 << (2 * k)];
+        for u in 0..self.num_vertices {
+            for v in 0..self.num_vertices {
+                if self.adjacency[u * self.num_vertices + v] {
+                    evaluations[u + (v << k)] = F::one();
+                }
+            }
+        }
+        DenseMultilinearExtension::from_evaluations_vec(2 * k, evaluations)
+    }
+
+    // A(x, y) A(y, z) A(x, z) over (x, y, z), x in the low bits
+    pub fn triangle_polynomial<F: Field>(&self) -> VirtualPolynomial<F> {
+        let k = self.vertex_vars();
+        let mask = (1 << k) - 1;
+        let adjacency = self.adjacency_mle::<F>();
+        let table = |pair: fn(usize, usize, usize) -> (usize, usize)| {
+            let evaluations = (0..1 << (3 * k))
+                .map(|index| {
+                    let (a, b) = pair(index & mask, (index >> k) & mask, index >> (2 * k));
+                    adjacency.evaluations[a + (b << k)]
+                })
+                .collect();
+            DenseMultilinearExtension::from_evaluations_vec(3 * k, evaluations)
+        };
+        VirtualPolynomial::new(
+            vec![table(|x, y, _| (x, y)), table(|_, y, z| (y, z)), table(|x, _, z| (x, z))],
+            vec![(F::one(), vec![0, 1, 2])],
+        )
+    }
+}
+
+impl<F: PrimeField> IPForSumCheck<F> {
+    // prove six times the number of triangles, given as the proof's claimed value
+    pub fn triangle_prove(
+        graph: &Graph,
+        transcript: &mut Transcript
+    ) -> SumcheckProof<F> {
+        Self::vp_prove(&graph.triangle_polynomial(), transcript)
+    }
+
+    // check the proof and return the number of triangles
+    pub fn triangle_verify(
+        graph: &Graph,
+        proof: &SumcheckProof<F>,
+        transcript: &mut Transcript
+    ) -> Result<u64, SumcheckError> {
+        let k = graph.vertex_vars();
+        // the proof carries the count, which is returned once the proof checks out
+        let subclaim = Self::verify(proof, proof.claimed_value, &vec![3; 3 * k], transcript)?;
+
+        let (x, rest) = subclaim.point.split_at(k);
+        let (y, z) = rest.split_at(k);
+        let adjacency = graph.adjacency_mle::<F>();
+        let at = |a: &[F], b: &[F]| adjacency.evaluate(&[a, b].concat()).unwrap();
+        if at(x, y) * at(y, z) * at(x, z) != subclaim.expected_evaluation {
+            return Err(SumcheckError::FinalEvaluationCheckFailed);
+        }
+
+        match field_to_u64(proof.claimed_value) {
+            Some(count) if count % 6 == 0 => Ok(count / 6),
+            _ => Err(SumcheckError::MalformedProof("claimed value is not six times a triangle count")),
+        }
+    }
+}
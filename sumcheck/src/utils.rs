@@ -0,0 +1,70 @@
+use ark_poly::{univariate::DensePolynomial, DenseUVPolynomial};
+use ark_ff::{Field, PrimeField};
+
+// helper function to recover the coefficients of a univariate polynomial of degree d
+// from its evaluations at 0, 1, ..., d
+pub fn interpolate_uni_poly<F: Field>(evaluations: &[F]) -> DensePolynomial<F> {
+    let mut coeffs = vec![F::zero(); evaluations.len()];
+    for (i, &value) in evaluations.iter().enumerate() {
+        // basis polynomial prod_{j != i} (X - j) / (i - j)
+        let mut basis = vec![F::one()];
+        let mut denominator = F::one();
+        for j in 0..evaluations.len() {
+            if i != j {
+                let mut next = vec![F::zero(); basis.len() + 1];
+                for (k, &coeff) in basis.iter().enumerate() {
+                    next[k] -= coeff * F::from(j as u64);
+                    next[k + 1] += coeff;
+                }
+                basis = next;
+                denominator *= F::from(i as u64) - F::from(j as u64);
+            }
+        }
+        let scale = value * denominator.inverse().unwrap();
+        for (acc, coeff) in coeffs.iter_mut().zip(basis) {
+            *acc += coeff * scale;
+        }
+    }
+    DensePolynomial::from_coefficients_vec(coeffs)
+}
+
+// evaluations of eq(x, point) over the boolean hypercube, where bit j of the index
+// gives the j-th coordinate of x. The table is built in place with one
+// multiplication per entry
+pub fn eq_table<F: Field>(point: &[F]) -> Vec<F> {
+    let mut table = vec![F::zero(); 1 << point.len()];
+    table[0] = F::one();
+    for (j, &r) in point.iter().enumerate() {
+        // split each entry over the first j coordinates into x_j = 0 and x_j = 1
+        let half = 1 << j;
+        for i in 0..half {
+            let high = table[i] * r;
+            table[i + half] = high;
+            table[i] -= high;
+        }
+    }
+    table
+}
+
+// eq(index, point) for a single boolean point given by the bits of index
+pub fn eq_eval_at_index<F: Field>(index: usize, point: &[F]) -> F {
+    point.iter().enumerate().fold(F::one(), |acc, (j, &r)| {
+        if (index >> j) & 1 == 1 { acc * r } else { acc * (F::one() - r) }
+    })
+}
+
+// eq(x, y) for two points of the same length
+pub fn eq_eval<F: Field>(x: &[F], y: &[F]) -> F {
+    assert_eq!(x.len(), y.len(), "points must have the same length");
+    x.iter().zip(y).fold(F::one(), |acc, (&x, &y)| acc * (x * y + (F::one() - x) * (F::one() - y)))
+}
+
+// small integer held by a field element, if it fits into 64 bits
+pub fn field_to_u64<F: PrimeField>(value: F) -> Option<u64> {
+    let bigint = value.into_bigint();
+    if bigint.as_ref()[1..].iter().all(|&limb| limb == 0) {
+        Some(bigint.as_ref()[0])
+    } else {
+        None
+    }
+}
@@ -1,78 +1,190 @@
-use ark_poly::{univariate::DensePolynomial, multivariate::{SparsePolynomial, SparseTerm}};
+use ark_poly::{univariate::DensePolynomial, multivariate::{SparsePolynomial, SparseTerm, Term}};
 use ark_poly::{Polynomial, DenseMVPolynomial};
 use ark_ff::Field;
 use ark_serialize::{CanonicalSerialize, CanonicalDeserialize};
 use ark_std::rand::RngCore;
 use crate::IPForSumCheck;
+use crate::error::SumcheckError;
+use crate::oracle::EvaluationOracle;
 
 #[derive(Clone, CanonicalSerialize, CanonicalDeserialize)]
 pub struct VerifierMsg<F: Field> {
     pub randomn_value: F,
 }
 
+/// claim left to the verifier after all rounds: the polynomial evaluates to
+/// `expected_evaluation` at `point`
+#[derive(Clone, Debug, PartialEq)]
+pub struct SubClaim<F: Field> {
+    /// randomness r1,...,rv sampled over the rounds
+    pub point: Vec<F>,
+    pub expected_evaluation: F,
+}
+
+#[derive(Clone, CanonicalSerialize, CanonicalDeserialize)]
 pub struct VerifierState<F: Field> {
     /// univariate polynomials sent by the prover at each round
     last_polynomial: Option<DensePolynomial<F>>,
     claimed_value: F,
+    /// largest power of each variable, bounding the degree of its round polynomial
+    degrees: Vec<usize>,
+    /// set H the sum runs over in every variable
+    pub(crate) domain: Vec<F>,
     /// randomness sampled by the verifier at each round
     randomness: Vec<F>,
     round: usize,
 }
 
+// largest power of each variable over the terms of the polynomial
+pub fn variable_degrees<F: Field>(polynomial: &SparsePolynomial<F, SparseTerm>) -> Vec<usize> {
+    let mut degrees = vec![0; polynomial.num_vars()];
+    for (_, term) in polynomial.terms() {
+        for (&var, pow) in term.vars().iter().zip(term.powers()) {
+            degrees[var] = degrees[var].max(pow);
+        }
+    }
+    degrees
+}
+
 impl<F: Field> IPForSumCheck<F> {
     pub fn verifier_init(
         polynomial:&SparsePolynomial<F, SparseTerm>, 
         claimed_value: F
+    ) -> VerifierState<F> {
+        Self::verifier_init_with_degrees(variable_degrees(polynomial), claimed_value)
+    }
+
+    pub fn verifier_init_with_degrees(
+        degrees: Vec<usize>,
+        claimed_value: F
     ) -> VerifierState<F> {
         VerifierState {
             last_polynomial: None,
-            claimed_value: claimed_value,
-            randomness: Vec::with_capacity(polynomial.num_vars()),
+            claimed_value,
+            randomness: Vec::with_capacity(degrees.len()),
+            degrees,
+            domain: vec![F::zero(), F::one()],
             round: 0,
         }
     }
 
+    // verifier for the sum over H^m instead of the boolean hypercube
+    pub fn verifier_init_with_domain(
+        polynomial: &SparsePolynomial<F, SparseTerm>,
+        claimed_value: F,
+        domain: &[F]
+    ) -> VerifierState<F> {
+        let mut verifier_state = Self::verifier_init(polynomial, claimed_value);
+        verifier_state.domain = domain.to_vec();
+        verifier_state
+    }
+
     pub fn verify_round<R: RngCore>(
         verifier_state: &mut VerifierState<F>, 
         polynomial:&DensePolynomial<F>, 
         rng:&mut R
-    ) -> Result<VerifierMsg<F>, &'static str> {
-        if verifier_state.round == 0 {
-            // check C1 = g1(0) +g1(1),
-            let eval = polynomial.evaluate(&F::zero()) + polynomial.evaluate(&F::one());
+    ) -> Result<VerifierMsg<F>, SumcheckError> {
+        Self::check_round(verifier_state, polynomial)?;
+        Ok(Self::accept_round(verifier_state, polynomial, F::rand(rng)))
+    }
+
+    // check the round polynomial against the claim carried over from the previous round
+    pub(crate) fn check_round(
+        verifier_state: &VerifierState<F>,
+        polynomial: &DensePolynomial<F>
+    ) -> Result<(), SumcheckError> {
+        let round = verifier_state.round;
+        if round >= verifier_state.degrees.len() {
+            return Err(SumcheckError::WrongNumberOfRounds {
+                expected: verifier_state.degrees.len(),
+                actual: round + 1,
+            });
+        }
+        // a higher degree would let the prover match the claim at more challenges
+        if polynomial.degree() > verifier_state.degrees[round] {
+            return Err(SumcheckError::DegreeTooHigh {
+                round,
+                degree: polynomial.degree(),
+                max_degree: verifier_state.degrees[round],
+            });
+        }
+
+        let eval: F = verifier_state.domain.iter().map(|h| polynomial.evaluate(h)).sum();
+        if round == 0 {
+            // check C1 = sum_{h in H} g1(h), g1(0) + g1(1) on the hypercube
             if eval !=  verifier_state.claimed_value {
-                return Err("First Evaluation Check Failed");
+                return Err(SumcheckError::FirstEvaluationCheckFailed);
             }
         } else {
-            // check gj−1(rj−1) = gj(0) +gj(1)
+            // check gj−1(rj−1) = sum_{h in H} gj(h)
             let last_polynomial = verifier_state.last_polynomial.clone().unwrap();
-            let eval = polynomial.evaluate(&F::zero()) + polynomial.evaluate(&F::one());
-            if eval != last_polynomial.evaluate(&verifier_state.randomness[verifier_state.round - 1]) {
-                return Err("Round Evaluation Check Failed");
+            if eval != last_polynomial.evaluate(&verifier_state.randomness[round - 1]) {
+                return Err(SumcheckError::RoundEvaluationCheckFailed { round });
             }
         }
-        let r = F::rand(rng);
+        Ok(())
+    }
+
+    // value the next round polynomial must sum to over the domain
+    pub(crate) fn current_claim(
+        verifier_state: &VerifierState<F>
+    ) -> F {
+        match &verifier_state.last_polynomial {
+            Some(last_polynomial) => last_polynomial.evaluate(&verifier_state.randomness[verifier_state.round - 1]),
+            None => verifier_state.claimed_value,
+        }
+    }
+
+    // record a checked round polynomial together with the challenge sent back
+    pub(crate) fn accept_round(
+        verifier_state: &mut VerifierState<F>,
+        polynomial: &DensePolynomial<F>,
+        r: F
+    ) -> VerifierMsg<F> {
         verifier_state.last_polynomial = Some(polynomial.clone());
-        verifier_state.randomness.push(r.clone());
+        verifier_state.randomness.push(r);
         verifier_state.round += 1;
 
-        Ok(VerifierMsg {
+        VerifierMsg {
             randomn_value: r,
-        })
+        }
+    }
+
+    // final claim g(r1,...,rv) = gv(rv) left once every round has been checked
+    pub(crate) fn subclaim(
+        verifier_state: &VerifierState<F>
+    ) -> Result<SubClaim<F>, SumcheckError> {
+        if verifier_state.round != verifier_state.degrees.len() {
+            return Err(SumcheckError::WrongNumberOfRounds {
+                expected: verifier_state.degrees.len(),
+                actual: verifier_state.round,
+            });
+        }
+        match &verifier_state.last_polynomial {
+            Some(last_polynomial) => Ok(SubClaim {
+                point: verifier_state.randomness.clone(),
+                expected_evaluation: last_polynomial.evaluate(&verifier_state.randomness[verifier_state.round - 1]),
+            }),
+            None => Err(SumcheckError::MalformedProof("no rounds were verified")),
+        }
     }
     pub fn verify_last_round(
         verifier_state: &mut VerifierState<F>,
         polynomial:&SparsePolynomial<F, SparseTerm>
-    ) -> Result<(), &'static str> {
-        // check gv(rv) = g(r1,...,rv),
-        let rv = verifier_state.randomness[verifier_state.round - 1].clone();
-        let expected = polynomial.evaluate(&verifier_state.randomness);
-        let eval = verifier_state.last_polynomial.clone().unwrap().evaluate(&rv);
-        if eval == expected {
+    ) -> Result<(), SumcheckError> {
+        Self::verify_last_round_with_oracle(verifier_state, polynomial)
+    }
+
+    // check gv(rv) = g(r1,...,rv) through an oracle instead of evaluating g
+    pub fn verify_last_round_with_oracle(
+        verifier_state: &mut VerifierState<F>,
+        oracle: &impl EvaluationOracle<F>
+    ) -> Result<(), SumcheckError> {
+        let subclaim = Self::subclaim(verifier_state)?;
+        if oracle.check_evaluation(&subclaim.point, subclaim.expected_evaluation) {
             Ok(())
         } else {
-            Err("Final Evaluation Check Failed")
+            Err(SumcheckError::FinalEvaluationCheckFailed)
         }
     }
 }
-
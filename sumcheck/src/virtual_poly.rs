@@ -0,0 +1,243 @@
+//! Sumcheck for sums of products of multilinear polynomials, as needed by GKR, Spartan
+//! and HyperPlonk. The polynomial is kept "virtual": a list of multilinear tables and a
+//! list of terms `c * f_i1 * ... * f_ik`. The prover sends each round polynomial as its
+//! evaluations at 0, 1, ..., d, where d is the largest number of tables in a term, and
+//! folds every table with each challenge as the multilinear prover does.
+
+use ark_poly::{univariate::DensePolynomial, DenseMultilinearExtension, MultilinearExtension};
+use ark_ff::Field;
+use ark_std::rand::RngCore;
+use crate::IPForSumCheck;
+use crate::error::SumcheckError;
+use crate::prover::ProverMsg;
+use crate::proof::SumcheckProof;
+use crate::transcript::Transcript;
+use crate::utils::interpolate_uni_poly;
+use crate::verifier::{VerifierMsg, VerifierState};
+
+#[derive(Clone)]
+pub struct VirtualPolynomial<F: Field> {
+    /// number of variables of every table
+    pub num_vars: usize,
+    /// multilinear tables referenced by the terms
+    pub mles: Vec<DenseMultilinearExtension<F>>,
+    /// terms given as a coefficient and the indices of the multiplied tables
+    pub products: Vec<(F, Vec<usize>)>,
+}
+
+/// public information the verifier needs about a virtual polynomial
+#[derive(Clone, Debug, PartialEq)]
+pub struct VPAuxInfo {
+    pub num_vars: usize,
+    /// degree of the round polynomials, the largest number of tables in a term
+    pub max_degree: usize,
+}
+
+impl VPAuxInfo {
+    // degree bound of every variable, as expected by `verify`
+    pub fn degrees(&self) -> Vec<usize> {
+        vec![self.max_degree; self.num_vars]
+    }
+}
+
+pub struct VPProverState<F: Field> {
+    /// tables with the bound variables fixed to the randomness
+    pub tables: Vec<Vec<F>>,
+    /// terms given as a coefficient and the indices of the multiplied tables
+    pub products: Vec<(F, Vec<usize>)>,
+    /// number of variables
+    pub num_vars: usize,
+    /// degree of the round polynomials
+    pub max_degree: usize,
+    /// randomness given by the verifier at each round
+    pub randomness: Vec<F>,
+    /// current round number
+    pub round: usize,
+}
+
+pub struct VPProverMsg<F: Field> {
+    /// evaluations of the round polynomial at 0, 1, ..., max_degree
+    pub evaluations: Vec<F>,
+}
+
+impl<F: Field> VirtualPolynomial<F> {
+    pub fn new(
+        mles: Vec<DenseMultilinearExtension<F>>,
+        products: Vec<(F, Vec<usize>)>
+    ) -> Self {
+        assert!(!mles.is_empty(), "virtual polynomial needs at least one table");
+        let mut polynomial = VirtualPolynomial {
+            num_vars: mles[0].num_vars,
+            mles: vec![],
+            products: vec![],
+        };
+        for mle in mles {
+            polynomial.add_mle(mle);
+        }
+        for (coefficient, indices) in products {
+            polynomial.add_product(coefficient, indices);
+        }
+        polynomial
+    }
+
+    // add a table and return its index
+    pub fn add_mle(&mut self, mle: DenseMultilinearExtension<F>) -> usize {
+        assert_eq!(mle.num_vars, self.num_vars, "tables must have the same number of variables");
+        self.mles.push(mle);
+        self.mles.len() - 1
+    }
+
+    pub fn add_product(&mut self, coefficient: F, indices: Vec<usize>) {
+        assert!(!indices.is_empty(), "term must multiply at least one table");
+        assert!(indices.iter().all(|&i| i < self.mles.len()), "term refers to a missing table");
+        self.products.push((coefficient, indices));
+    }
+
+    pub fn max_degree(&self) -> usize {
+        self.products.iter().map(|(_, indices)| indices.len()).max().unwrap_or(0)
+    }
+
+    pub fn aux_info(&self) -> VPAuxInfo {
+        VPAuxInfo {
+            num_vars: self.num_vars,
+            max_degree: self.max_degree(),
+        }
+    }
+
+    pub fn evaluate(&self, point: &[F]) -> F {
+        let evaluations: Vec<F> = self.mles.iter()
+            .map(|mle| mle.evaluate(point).unwrap())
+            .collect();
+        self.products.iter()
+            .map(|(coefficient, indices)| {
+                indices.iter().fold(*coefficient, |acc, &i| acc * evaluations[i])
+            })
+            .sum()
+    }
+}
+
+impl<F: Field> VPProverMsg<F> {
+    pub fn to_uvpolynomial(&self) -> DensePolynomial<F> {
+        interpolate_uni_poly(&self.evaluations)
+    }
+}
+
+impl<F: Field> IPForSumCheck<F> {
+    pub fn vp_prover_init(
+        polynomial: &VirtualPolynomial<F>
+    ) -> VPProverState<F> {
+        if polynomial.num_vars == 0 {
+            panic!("polynomial must have at least one variable");
+        }
+        VPProverState {
+            tables: polynomial.mles.iter().map(|mle| mle.evaluations.clone()).collect(),
+            products: polynomial.products.clone(),
+            num_vars: polynomial.num_vars,
+            max_degree: polynomial.max_degree(),
+            randomness: Vec::with_capacity(polynomial.num_vars),
+            round: 0,
+        }
+    }
+
+    pub fn vp_run_init_prover_round(
+        prover_state: &mut VPProverState<F>,
+    ) -> F {
+        let size = 1 << prover_state.num_vars;
+        let mut accum = F::zero();
+        for (coefficient, indices) in &prover_state.products {
+            for b in 0..size {
+                accum += indices.iter().fold(*coefficient, |acc, &i| acc * prover_state.tables[i][b]);
+            }
+        }
+        accum
+    }
+
+    pub fn vp_prove_round(
+        prover_state: &mut VPProverState<F>,
+        verifier_msg: &Option<VerifierMsg<F>>
+    ) -> VPProverMsg<F> {
+        // fix the variable of the previous round to the challenge in every table
+        if let Some(msg) = verifier_msg {
+            let r = msg.randomn_value;
+            for table in prover_state.tables.iter_mut() {
+                let half = table.len() / 2;
+                for i in 0..half {
+                    table[i] = table[2 * i] + r * (table[2 * i + 1] - table[2 * i]);
+                }
+                table.truncate(half);
+            }
+            prover_state.randomness.push(r);
+        }
+
+        // evaluate each term at X = 0, 1, ..., d by walking along the line between
+        // the even and odd entries of its tables
+        let degree = prover_state.max_degree;
+        let half = prover_state.tables[0].len() / 2;
+        let mut evaluations = vec![F::zero(); degree + 1];
+        for (coefficient, indices) in &prover_state.products {
+            for b in 0..half {
+                let mut values: Vec<F> = indices.iter().map(|&i| prover_state.tables[i][2 * b]).collect();
+                let steps: Vec<F> = indices.iter()
+                    .map(|&i| prover_state.tables[i][2 * b + 1] - prover_state.tables[i][2 * b])
+                    .collect();
+                for evaluation in evaluations.iter_mut() {
+                    *evaluation += values.iter().fold(*coefficient, |acc, value| acc * value);
+                    for (value, step) in values.iter_mut().zip(&steps) {
+                        *value += step;
+                    }
+                }
+            }
+        }
+        prover_state.round += 1;
+
+        VPProverMsg { evaluations }
+    }
+
+    pub fn vp_verifier_init(
+        aux_info: &VPAuxInfo,
+        claimed_value: F
+    ) -> VerifierState<F> {
+        Self::verifier_init_with_degrees(aux_info.degrees(), claimed_value)
+    }
+
+    pub fn vp_verify_round<R: RngCore>(
+        verifier_state: &mut VerifierState<F>,
+        aux_info: &VPAuxInfo,
+        prover_msg: &VPProverMsg<F>,
+        rng: &mut R
+    ) -> Result<VerifierMsg<F>, SumcheckError> {
+        if prover_msg.evaluations.len() != aux_info.max_degree + 1 {
+            return Err(SumcheckError::MalformedProof("round message has the wrong number of evaluations"));
+        }
+        Self::verify_round(verifier_state, &prover_msg.to_uvpolynomial(), rng)
+    }
+
+    pub fn vp_verify_last_round(
+        verifier_state: &mut VerifierState<F>,
+        polynomial: &VirtualPolynomial<F>
+    ) -> Result<(), SumcheckError> {
+        Self::verify_last_round_with_oracle(verifier_state, polynomial)
+    }
+
+    // non-interactive proof for a virtual polynomial, verified with `verify`
+    pub fn vp_prove(
+        polynomial: &VirtualPolynomial<F>,
+        transcript: &mut Transcript
+    ) -> SumcheckProof<F> {
+        Self::vp_prove_with_randomness(polynomial, transcript).0
+    }
+
+    // same as vp_prove, also returning the point the sumcheck reduced to
+    pub(crate) fn vp_prove_with_randomness(
+        polynomial: &VirtualPolynomial<F>,
+        transcript: &mut Transcript
+    ) -> (SumcheckProof<F>, Vec<F>) {
+        let mut prover_state = Self::vp_prover_init(polynomial);
+        let claimed_value = Self::vp_run_init_prover_round(&mut prover_state);
+        Self::prove_rounds(&polynomial.aux_info().degrees(), claimed_value, transcript, |verifier_msg| {
+            ProverMsg {
+                uvpolynomial: Self::vp_prove_round(&mut prover_state, verifier_msg).to_uvpolynomial(),
+            }
+        })
+    }
+}
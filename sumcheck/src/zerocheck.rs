@@ -0,0 +1,68 @@
+//! Zerocheck: proof that a virtual polynomial f vanishes on the whole hypercube
+//! (HyperPlonk, section 3.2).
+//!
+//! The verifier picks a random point r and the sumcheck runs on `f(x) eq(x, r)` with
+//! claimed sum 0. This sum is the multilinear extension of f's hypercube values at r,
+//! so it is 0 for every r only if f vanishes on the hypercube, and a non-vanishing f
+//! passes with probability at most n / |F|. The final claim about `f eq` is turned
+//! into a claim about f, since the verifier evaluates `eq(point, r)` itself.
+
+use ark_poly::DenseMultilinearExtension;
+use ark_ff::Field;
+use crate::IPForSumCheck;
+use crate::error::SumcheckError;
+use crate::proof::SumcheckProof;
+use crate::transcript::Transcript;
+use crate::utils::{eq_eval, eq_table};
+use crate::verifier::SubClaim;
+use crate::virtual_poly::{VirtualPolynomial, VPAuxInfo};
+
+impl<F: Field> IPForSumCheck<F> {
+    pub fn zerocheck_prove(
+        polynomial: &VirtualPolynomial<F>,
+        transcript: &mut Transcript
+    ) -> SumcheckProof<F> {
+        Self::zerocheck_prove_with_randomness(polynomial, transcript).0
+    }
+
+    // same as zerocheck_prove, also returning the point the sumcheck reduced to
+    pub(crate) fn zerocheck_prove_with_randomness(
+        polynomial: &VirtualPolynomial<F>,
+        transcript: &mut Transcript
+    ) -> (SumcheckProof<F>, Vec<F>) {
+        let r = zerocheck_point(&polynomial.aux_info(), transcript);
+
+        // multiply every term by eq(x, r)
+        let mut masked = polynomial.clone();
+        let eq = masked.add_mle(DenseMultilinearExtension::from_evaluations_vec(polynomial.num_vars, eq_table(&r)));
+        for (_, indices) in masked.products.iter_mut() {
+            indices.push(eq);
+        }
+        Self::vp_prove_with_randomness(&masked, transcript)
+    }
+
+    // check that the sum of f eq(x, r) is 0 and return the claim about f at the
+    // sumcheck point
+    pub fn zerocheck_verify(
+        proof: &SumcheckProof<F>,
+        aux_info: &VPAuxInfo,
+        transcript: &mut Transcript
+    ) -> Result<SubClaim<F>, SumcheckError> {
+        let r = zerocheck_point(aux_info, transcript);
+        let subclaim = Self::verify(proof, F::zero(), &vec![aux_info.max_degree + 1; aux_info.num_vars], transcript)?;
+        let eq = eq_eval(&subclaim.point, &r);
+        // eq(point, r) is 0 only with negligible probability
+        let inverse = eq.inverse().ok_or(SumcheckError::FinalEvaluationCheckFailed)?;
+        Ok(SubClaim {
+            point: subclaim.point,
+            expected_evaluation: subclaim.expected_evaluation * inverse,
+        })
+    }
+}
+
+// absorb the shape of the polynomial, then derive the point r
+fn zerocheck_point<F: Field>(aux_info: &VPAuxInfo, transcript: &mut Transcript) -> Vec<F> {
+    transcript.append_serializable(b"zerocheck_num_vars", &(aux_info.num_vars as u64));
+    transcript.append_serializable(b"zerocheck_max_degree", &(aux_info.max_degree as u64));
+    (0..aux_info.num_vars).map(|_| transcript.challenge_scalar(b"zerocheck_challenge")).collect()
+}
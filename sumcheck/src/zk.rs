@@ -0,0 +1,125 @@
+//! Zero-knowledge sumcheck for committed multilinear polynomials with a masking
+//! polynomial (Chiesa, Forbes and Spooner, 2017; Xie et al., Libra).
+//!
+//! The prover commits to a random multilinear mask p and sends its sum P. After the
+//! verifier's challenge rho, the sumcheck runs on g + rho p for the claim H + rho P.
+//! The final claim is settled by opening g + rho p at the sumcheck point against
+//! `C_g + rho C_p`, which the verifier derives from the two commitments since
+//! multilinear KZG commitments are linear, so neither g(r) nor p(r) is sent.
+//!
+//! For rho != 0 the masked polynomial g + rho p is uniformly random given its sum, and
+//! the whole proof (the mask commitment and sum, the round polynomials and the opening)
+//! is a function of the masked polynomial, the commitment to g and rho. A simulator
+//! knowing only `C_g` and H samples the masked polynomial m and sets
+//! `C_p = (C_m - C_g) / rho`, so the proof reveals nothing about g beyond its sum.
+
+use ark_poly::{DenseMultilinearExtension, MultilinearExtension};
+use ark_ec::pairing::Pairing;
+use ark_ff::Field;
+use ark_std::rand::RngCore;
+use kzg::multilinear::MultilinearKZG;
+use crate::IPForSumCheck;
+use crate::error::SumcheckError;
+use crate::ml_prover::MLProverState;
+use crate::proof::SumcheckProof;
+use crate::transcript::Transcript;
+use crate::verifier::VerifierState;
+
+pub struct ZKSumcheckProof<E: Pairing> {
+    /// commitment to the masking polynomial
+    pub mask_commitment: E::G1,
+    /// sum of the masking polynomial over the hypercube
+    pub mask_sum: E::ScalarField,
+    /// sumcheck for g + rho p
+    pub sumcheck: SumcheckProof<E::ScalarField>,
+    /// opening of g + rho p at the point the sumcheck reduced to
+    pub proof: Vec<E::G1>,
+}
+
+impl<F: Field> IPForSumCheck<F> {
+    // prover for the masked polynomial g + rho p, run with the usual ml prover rounds
+    pub fn zk_prover_init(
+        polynomial: &DenseMultilinearExtension<F>,
+        mask: &DenseMultilinearExtension<F>,
+        rho: F
+    ) -> MLProverState<F> {
+        Self::ml_prover_init(&mask_polynomial(polynomial, mask, rho))
+    }
+
+    // verifier for the claim H + rho P about the masked polynomial
+    pub fn zk_verifier_init(
+        num_vars: usize,
+        claimed_value: F,
+        mask_sum: F,
+        rho: F
+    ) -> VerifierState<F> {
+        Self::verifier_init_with_degrees(vec![1; num_vars], claimed_value + rho * mask_sum)
+    }
+
+    // prove the sum of the polynomial behind the commitment
+    pub fn zk_prove<E: Pairing<ScalarField = F>, R: RngCore>(
+        polynomial: &DenseMultilinearExtension<F>,
+        pcs: &MultilinearKZG<E>,
+        commitment: E::G1,
+        transcript: &mut Transcript,
+        rng: &mut R
+    ) -> ZKSumcheckProof<E> {
+        let mask = DenseMultilinearExtension::rand(polynomial.num_vars, rng);
+        let mask_commitment = pcs.commit(&mask.evaluations);
+        let mask_sum: F = mask.evaluations.iter().sum();
+        transcript.append_serializable(b"commitment", &commitment);
+        transcript.append_serializable(b"mask_commitment", &mask_commitment);
+        transcript.append_serializable(b"mask_sum", &mask_sum);
+        let rho = transcript.challenge_scalar(b"mask_challenge");
+
+        let masked = mask_polynomial(polynomial, &mask, rho);
+        let (sumcheck, point) = Self::ml_prove_with_randomness(&masked, transcript);
+
+        ZKSumcheckProof {
+            mask_commitment,
+            mask_sum,
+            sumcheck,
+            proof: pcs.open(&masked.evaluations, &point),
+        }
+    }
+
+    // verify a claimed sum of the polynomial behind the commitment
+    pub fn zk_verify<E: Pairing<ScalarField = F>>(
+        proof: &ZKSumcheckProof<E>,
+        claimed_value: F,
+        pcs: &MultilinearKZG<E>,
+        commitment: E::G1,
+        transcript: &mut Transcript
+    ) -> Result<(), SumcheckError> {
+        transcript.append_serializable(b"commitment", &commitment);
+        transcript.append_serializable(b"mask_commitment", &proof.mask_commitment);
+        transcript.append_serializable(b"mask_sum", &proof.mask_sum);
+        let rho: F = transcript.challenge_scalar(b"mask_challenge");
+
+        let subclaim = Self::verify(
+            &proof.sumcheck,
+            claimed_value + rho * proof.mask_sum,
+            &vec![1; pcs.num_vars],
+            transcript
+        )?;
+
+        // (g + rho p)(r) is opened against the commitment C_g + rho C_p
+        let masked_commitment = commitment + proof.mask_commitment * rho;
+        if !pcs.verify(&subclaim.point, subclaim.expected_evaluation, masked_commitment, &proof.proof) {
+            return Err(SumcheckError::FinalEvaluationCheckFailed);
+        }
+        Ok(())
+    }
+}
+
+fn mask_polynomial<F: Field>(
+    polynomial: &DenseMultilinearExtension<F>,
+    mask: &DenseMultilinearExtension<F>,
+    rho: F
+) -> DenseMultilinearExtension<F> {
+    let evaluations = polynomial.evaluations.iter()
+        .zip(&mask.evaluations)
+        .map(|(&g, &p)| g + rho * p)
+        .collect();
+    DenseMultilinearExtension::from_evaluations_vec(polynomial.num_vars, evaluations)
+}